@@ -50,7 +50,7 @@ fn add_resources(source_path: &PathBuf, target_path: &PathBuf) -> io::Result<()>
             }
         }
         Err(_) => {
-            copy(&source_path, &target_path)?;
+            copy(source_path, target_path)?;
         }
     }
     Ok(())
@@ -0,0 +1,235 @@
+use boxcrash::game::{user_config_path, LayeredConfig};
+use boxcrash::{GameConfig, Pixel};
+use std::path::PathBuf;
+
+// Options given on the command line
+pub struct Options {
+    pub config: Option<PathBuf>, // Config file, the default one if not given
+    pub seed: Option<u64>,       // Seed of random generator
+    pub overrides: Vec<(String, String)>, // Config fields to override
+    pub size: Option<Pixel>,     // Window size
+    pub play: bool,              // Skip the start menu
+    pub replay: Option<PathBuf>, // Replay file to play
+    pub resume: Option<PathBuf>, // Snapshot file to continue
+    pub screenshot: Option<PathBuf>, // PNG or SVG file to render to, without a window
+    pub help: bool,
+}
+
+const OPTIONS: &str = "\
+Options:
+  --config FILE      Read config from FILE (default: resources/config.json)
+  --seed N           Seed of random generator
+  --set FIELD=VALUE  Override a config field, e.g. `--set bot_speed=30,90`
+  --size WxH         Window size, e.g. `--size 1280x720`
+  --play             Start playing without the start menu
+  --replay FILE      Play a replay file
+  --resume FILE      Continue a saved game
+  --screenshot FILE  Render the game to a PNG or SVG file (by FILE's extension)
+                     without opening a window, the end of a replay or a saved
+                     game if one is given
+  --help             Print this message
+
+Config is read in layers, each one overrides the fields it sets:
+the defaults below, the config file, the user's config file
+({user config}), then the command line.";
+
+// Return the help message, config fields are listed with
+// their default values.
+pub fn usage() -> String {
+    let fields = GameConfig::default()
+        .fields()
+        .into_iter()
+        .map(|(k, v)| format!("  {} = {}", k, v))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let user = user_config_path()
+        .map(|x| x.display().to_string())
+        .unwrap_or_else(|| "not found".to_owned());
+    format!(
+        "Usage: boxcrash [OPTIONS]\n\n{}\n\nConfig fields and their defaults:\n{}",
+        OPTIONS.replace("{user config}", &user),
+        fields
+    )
+}
+
+// Parse command line arguments, without the program's name
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        config: None,
+        seed: None,
+        overrides: Vec::new(),
+        size: None,
+        play: false,
+        replay: None,
+        resume: None,
+        screenshot: None,
+        help: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("`{}` requires a value", arg))
+        };
+        match arg.as_str() {
+            "--config" => options.config = Some(value()?.into()),
+            "--seed" => {
+                let x = value()?;
+                options.seed = Some(x.parse().map_err(|_| format!("Invalid seed `{}`", x))?);
+            }
+            "--set" => {
+                let x = value()?;
+                let mut split = x.splitn(2, '=');
+                match (split.next(), split.next()) {
+                    (Some(k), Some(v)) => options.overrides.push((k.to_owned(), v.to_owned())),
+                    _ => return Err(format!("`--set` requires FIELD=VALUE, got `{}`", x)),
+                }
+            }
+            "--size" => {
+                let x = value()?;
+                let mut split = x.splitn(2, 'x').map(|x| x.parse::<u32>().ok());
+                match (split.next(), split.next()) {
+                    (Some(Some(w)), Some(Some(h))) => options.size = Some(Pixel::new(w, h)),
+                    _ => return Err(format!("`--size` requires WxH, got `{}`", x)),
+                }
+            }
+            "--play" => options.play = true,
+            "--replay" => options.replay = Some(value()?.into()),
+            "--resume" => options.resume = Some(value()?.into()),
+            "--screenshot" => options.screenshot = Some(value()?.into()),
+            "--help" | "-h" => options.help = true,
+            _ => return Err(format!("Unknown option `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// Add the options that change the config as the last layer
+pub fn apply(options: &Options, config: &mut LayeredConfig) -> Result<(), String> {
+    const NAME: &str = "command line";
+    if let Some(seed) = options.seed {
+        config.set(NAME, "seed", &seed.to_string())?;
+    }
+    if let Some(size) = options.size {
+        config.set(NAME, "screen_size.w", &size.w.to_string())?;
+        config.set(NAME, "screen_size.h", &size.h.to_string())?;
+    }
+    for (k, v) in &options.overrides {
+        config.set(NAME, k, v)?;
+    }
+    Ok(())
+}
+
+// Config file read if `--config` is not given
+const DEFAULT_CONFIG: &str = "resources/config.json";
+
+// What to do when a config file can't be read
+#[derive(Clone, Copy, PartialEq)]
+pub enum Missing {
+    Error, // The file was given on the command line
+    Warn,  // The default config file, run with the default values
+    Ignore,
+}
+
+// Config files to read, with what to do if each one can't be read
+pub fn config_files(options: &Options) -> Vec<(PathBuf, Missing)> {
+    let mut files = vec![match &options.config {
+        Some(path) => (path.clone(), Missing::Error),
+        None => (PathBuf::from(DEFAULT_CONFIG), Missing::Warn),
+    }];
+    if let Some(path) = user_config_path() {
+        files.push((path, Missing::Ignore));
+    }
+    files
+}
+
+// Read config in layers: the defaults, the config file, the user's
+// config file, then the command line. On errors, return them with
+// the exit code to use.
+pub fn load_config(options: &Options) -> Result<GameConfig, (i32, Vec<String>)> {
+    let mut layers = LayeredConfig::new();
+    for (path, missing) in config_files(options) {
+        match std::fs::read_to_string(&path) {
+            Ok(source) => {
+                if let Err(errors) = layers.add_json(&path.display().to_string(), &source) {
+                    let errors = errors
+                        .into_iter()
+                        .map(|e| format!("Error in config file {}: {}", path.display(), e))
+                        .collect();
+                    return Err((1, errors));
+                }
+            }
+            Err(e) => match missing {
+                Missing::Error => {
+                    let error = format!("Cannot read config file {}: {}", path.display(), e);
+                    return Err((1, vec![error]));
+                }
+                Missing::Warn => eprintln!(
+                    "Warning: cannot read config file {}: {}, using the default values",
+                    path.display(),
+                    e
+                ),
+                Missing::Ignore => (),
+            },
+        }
+    }
+    apply(options, &mut layers).map_err(|e| (2, vec![e]))?;
+    layers.build().map_err(|errors| {
+        let errors = errors
+            .into_iter()
+            .map(|(name, e)| format!("Error in {}: {}", name, e))
+            .collect();
+        (1, errors)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(x: &str) -> impl Iterator<Item = String> + '_ {
+        x.split_whitespace().map(|x| x.to_owned())
+    }
+
+    #[test]
+    fn options_are_read() {
+        let options = parse(args(
+            "--config a.json --seed 5 --size 640x480 --set ups=30 --play --replay r.json",
+        ))
+        .unwrap();
+        assert_eq!(options.config, Some("a.json".into()));
+        assert_eq!(options.seed, Some(5));
+        assert!(options.size == Some(Pixel::new(640, 480)));
+        assert_eq!(options.overrides, [("ups".to_owned(), "30".to_owned())]);
+        assert!(options.play);
+        assert_eq!(options.replay, Some("r.json".into()));
+        assert!(!options.help);
+    }
+
+    #[test]
+    fn bad_options_are_errors() {
+        for x in &[
+            "--seed",
+            "--seed x",
+            "--size 640",
+            "--size 640xy",
+            "--set ups",
+            "--fast",
+        ] {
+            assert!(parse(args(x)).is_err(), "`{}` was accepted", x);
+        }
+    }
+
+    #[test]
+    fn command_line_is_the_last_layer() {
+        let options = parse(args("--seed 5 --size 640x480 --set screen_size.w=1024")).unwrap();
+        let mut layers = LayeredConfig::new();
+        layers
+            .add_json("file", r#"{"seed": 1, "ups": 30}"#)
+            .unwrap();
+        apply(&options, &mut layers).unwrap();
+        let config = layers.build().ok().unwrap();
+        assert_eq!(config.seed, Some(5));
+        assert_eq!(config.ups, 30);
+        assert!(config.screen_size == Pixel::new(1024, 480));
+    }
+}
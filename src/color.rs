@@ -1,4 +1,4 @@
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Color([f32; 4]);
 
 impl Color {
@@ -7,21 +7,41 @@ impl Color {
         (self.0)[3] = a;
         self
     }
+    // Multiply red, green and blue chanels by `k`
+    pub fn scale(mut self, k: f32) -> Self {
+        for x in &mut self.0[..3] {
+            *x = (*x * k).min(1.);
+        }
+        self
+    }
+    // Multiply alpha chanel by `k`
+    pub fn fade(mut self, k: f32) -> Self {
+        (self.0)[3] *= k;
+        self
+    }
+    // Move red, green and blue chanels toward `other`'s by `t`,
+    // from 0 to 1
+    pub fn mix(mut self, other: Color, t: f32) -> Self {
+        for (x, y) in self.0[..3].iter_mut().zip(&other.0[..3]) {
+            *x += (*y - *x) * t;
+        }
+        self
+    }
 }
 
-impl Into<Color> for [f32; 4] {
-    fn into(self) -> Color {
-        Color(self)
+impl From<[f32; 4]> for Color {
+    fn from(x: [f32; 4]) -> Color {
+        Color(x)
     }
 }
-impl Into<[f32; 4]> for Color {
-    fn into(self) -> [f32; 4] {
-        self.0
+impl From<Color> for [f32; 4] {
+    fn from(x: Color) -> [f32; 4] {
+        x.0
     }
 }
-impl Into<::conrod::Color> for Color {
-    fn into(self) -> ::conrod::Color {
-        let c = self.0;
+impl From<Color> for ::conrod::Color {
+    fn from(x: Color) -> ::conrod::Color {
+        let c = x.0;
         ::conrod::Color::Rgba(c[0], c[1], c[2], c[3])
     }
 }
@@ -1,59 +1,70 @@
-use conrod;
 use conrod::text::GlyphCache;
 use conrod::widget;
 use conrod::UiCell;
-use piston_window;
 use piston_window::texture::UpdateTexture;
 use piston_window::{Event, G2d, G2dTexture, PistonWindow, TextureSettings, UpdateEvent, Window};
 
 use crate::control::{EventHandler, Flow};
+use boxcrash::Pixel;
 
 pub trait Gui {
     type Ids;
 
     fn new() -> Self;
-    fn gui(&mut self, _: &mut UiCell, _: &Self::Ids) -> Option<Flow>;
+    // `size` is the window's size
+    fn gui(&mut self, _: &mut UiCell, _: &Self::Ids, size: Pixel) -> Option<Flow>;
     fn ids(&self, _: widget::id::Generator) -> Self::Ids;
 }
 
 pub struct ConrodUI<'a, G: Gui> {
     gui: G,
     ids: G::Ids,
+    size: Pixel, // Size of the caches
     glyph_cache: GlyphCache<'a>,
     text_texture_cache: G2dTexture,
 }
 
 impl<'a, G: Gui> ConrodUI<'a, G> {
-    pub fn new(
-        size: crate::Pixel,
-        window: &mut PistonWindow,
-        ui: &mut conrod::Ui,
-    ) -> ConrodUI<'a, G> {
+    pub fn new(size: Pixel, window: &mut PistonWindow, ui: &mut conrod::Ui) -> ConrodUI<'a, G> {
         let gui = G::new();
-
-        // Create a texture to use for efficiently caching text on the GPU.
-        let (glyph_cache, text_texture_cache) = {
-            const SCALE_TOLERANCE: f32 = 0.1;
-            const POSITION_TOLERANCE: f32 = 0.1;
-            let cache = GlyphCache::new(size.w, size.h, SCALE_TOLERANCE, POSITION_TOLERANCE);
-            let buffer_len = size.w as usize * size.h as usize;
-            let init = vec![128; buffer_len];
-            let settings = TextureSettings::new();
-            let factory = &mut window.factory;
-            let texture =
-                G2dTexture::from_memory_alpha(factory, &init, size.w, size.h, &settings).unwrap();
-            (cache, texture)
-        };
-
+        let (glyph_cache, text_texture_cache) = caches(size, window);
         let ids = gui.ids(ui.widget_id_generator());
 
         ConrodUI {
             gui,
             ids,
+            size,
             glyph_cache,
             text_texture_cache,
         }
     }
+
+    // Follow the window's size, it may have changed while another
+    // state was handling events.
+    fn fit(&mut self, window: &mut PistonWindow, ui: &mut conrod::Ui) {
+        let size = window.size();
+        let size = Pixel::new(size.width, size.height);
+        if size != self.size && size.w > 0 && size.h > 0 {
+            let (glyph_cache, text_texture_cache) = caches(size, window);
+            self.glyph_cache = glyph_cache;
+            self.text_texture_cache = text_texture_cache;
+            self.size = size;
+            ui.handle_event(conrod::event::Input::Resize(size.w, size.h));
+        }
+    }
+}
+
+// Create a texture to use for efficiently caching text on the GPU.
+fn caches<'a>(size: Pixel, window: &mut PistonWindow) -> (GlyphCache<'a>, G2dTexture) {
+    const SCALE_TOLERANCE: f32 = 0.1;
+    const POSITION_TOLERANCE: f32 = 0.1;
+    let cache = GlyphCache::new(size.w, size.h, SCALE_TOLERANCE, POSITION_TOLERANCE);
+    let buffer_len = size.w as usize * size.h as usize;
+    let init = vec![128; buffer_len];
+    let settings = TextureSettings::new();
+    let factory = &mut window.factory;
+    let texture = G2dTexture::from_memory_alpha(factory, &init, size.w, size.h, &settings).unwrap();
+    (cache, texture)
 }
 
 impl<'a, G: Gui> EventHandler for ConrodUI<'a, G> {
@@ -64,6 +75,7 @@ impl<'a, G: Gui> EventHandler for ConrodUI<'a, G> {
         window: &mut PistonWindow,
         ui: &mut Self::Input,
     ) -> Option<Flow> {
+        self.fit(window, ui);
         let size = window.size();
         let (win_w, win_h) = (f64::from(size.width), f64::from(size.height));
         if let Some(e) = conrod::backend::piston::event::convert(event.clone(), win_w, win_h) {
@@ -73,7 +85,7 @@ impl<'a, G: Gui> EventHandler for ConrodUI<'a, G> {
         let mut flow = None;
         event.update(|_| {
             let mut ui = ui.set_widgets();
-            flow = self.gui.gui(&mut ui, &self.ids);
+            flow = self.gui.gui(&mut ui, &self.ids, self.size);
         });
 
         window.draw_2d(&event, |context, graphics| {
@@ -1,9 +1,11 @@
+use boxcrash::Pixel;
 use piston_window::{Event, PistonWindow};
 
 pub enum Flow {
     StartGame,
     PlayAgain,
     LoseGame,
+    SetResolution(Pixel), // Resize the window and save the size to config
 }
 
 pub enum State {
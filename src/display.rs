@@ -0,0 +1,179 @@
+// Display lists: the shapes a frame is made of, in window's
+// coordinates, for any backend to draw in order.
+use crate::color::Color;
+use cgmath::prelude::*;
+use cgmath::{vec2, Vector2};
+
+// How a line is drawn
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Stroke {
+    Solid,
+    Dashed { dash: f64, gap: f64 }, // Lengths in pixels
+}
+
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Line {
+        points: [Vector2<f64>; 2],
+        width: f64,
+        stroke: Stroke,
+    },
+    // A filled convex polygon
+    Polygon(Vec<Vector2<f64>>),
+    Circle {
+        centre: Vector2<f64>,
+        radius: f64,
+        outline: Option<f64>, // Width of the outline, filled if not set
+    },
+    Text {
+        pos: Vector2<f64>, // Start of the baseline
+        size: f64,         // Font's height in pixels
+        text: String,
+    },
+}
+
+// A shape with its color and its distance from the camera, things
+// that aren't in the world (e.g. the HUD) are at depth 0.
+#[derive(Clone, Debug)]
+pub struct Primitive {
+    pub shape: Shape,
+    pub color: Color,
+    pub depth: f64,
+}
+
+pub type DisplayList = Vec<Primitive>;
+
+impl Primitive {
+    pub fn new(shape: Shape, color: Color) -> Primitive {
+        Primitive {
+            shape,
+            color,
+            depth: 0.,
+        }
+    }
+
+    pub fn line(points: [Vector2<f64>; 2], width: f64, color: Color) -> Primitive {
+        let stroke = Stroke::Solid;
+        Primitive::new(
+            Shape::Line {
+                points,
+                width,
+                stroke,
+            },
+            color,
+        )
+    }
+
+    pub fn polygon(points: Vec<Vector2<f64>>, color: Color) -> Primitive {
+        Primitive::new(Shape::Polygon(points), color)
+    }
+
+    // A rectangle given as [x, y, width, height]
+    pub fn rectangle([x, y, w, h]: [f64; 4], color: Color) -> Primitive {
+        let points = vec![
+            vec2(x, y),
+            vec2(x + w, y),
+            vec2(x + w, y + h),
+            vec2(x, y + h),
+        ];
+        Primitive::polygon(points, color)
+    }
+
+    pub fn circle(
+        centre: Vector2<f64>,
+        radius: f64,
+        outline: Option<f64>,
+        color: Color,
+    ) -> Primitive {
+        Primitive::new(
+            Shape::Circle {
+                centre,
+                radius,
+                outline,
+            },
+            color,
+        )
+    }
+
+    pub fn text(pos: Vector2<f64>, size: f64, text: &str, color: Color) -> Primitive {
+        let text = text.to_owned();
+        Primitive::new(Shape::Text { pos, size, text }, color)
+    }
+
+    pub fn at(mut self, depth: f64) -> Primitive {
+        self.depth = depth;
+        self
+    }
+
+    // Draw a line as dashes, other shapes are kept
+    pub fn dashed(mut self, dash: f64, gap: f64) -> Primitive {
+        if let Shape::Line { stroke, .. } = &mut self.shape {
+            *stroke = Stroke::Dashed { dash, gap };
+        }
+        self
+    }
+}
+
+// Clip the segment `a`-`b` to the rectangle from `min` to `max`
+// (Liang-Barsky), return None if nothing is left.
+pub fn clip(
+    a: Vector2<f64>,
+    b: Vector2<f64>,
+    min: Vector2<f64>,
+    max: Vector2<f64>,
+) -> Option<[Vector2<f64>; 2]> {
+    let d = b - a;
+    let (mut t0, mut t1) = (0f64, 1f64);
+    for &(p, q) in &[
+        (-d.x, a.x - min.x),
+        (d.x, max.x - a.x),
+        (-d.y, a.y - min.y),
+        (d.y, max.y - a.y),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0. {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+    if t0 > t1 {
+        None
+    } else {
+        Some([a + t0 * d, a + t1 * d])
+    }
+}
+
+// Cut the part of a line inside the rectangle from `min` to `max`
+// into dashes, for backends that can't draw them.
+pub fn dashes(
+    [a, b]: [Vector2<f64>; 2],
+    dash: f64,
+    gap: f64,
+    min: Vector2<f64>,
+    max: Vector2<f64>,
+) -> Vec<[Vector2<f64>; 2]> {
+    // Only the part on the screen is worth cutting
+    let [a, b] = match clip(a, b, min, max) {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+    if dash <= 0. || gap <= 0. {
+        return vec![[a, b]];
+    }
+    let len = (b - a).magnitude();
+    let mut ret = Vec::new();
+    let mut x = 0.;
+    while x < len {
+        let end = f64::min(x + dash, len);
+        ret.push([a + (b - a) * (x / len), a + (b - a) * (end / len)]);
+        x += dash + gap;
+    }
+    ret
+}
@@ -0,0 +1,360 @@
+use crate::control::{EventHandler, Flow};
+use boxcrash::color::*;
+use boxcrash::display::{dashes, DisplayList, Primitive, Shape, Stroke};
+use boxcrash::game::{Command, Control, FixedStep, Frame, Playback, Replay, Simulation, Snapshot};
+use boxcrash::raster::{self, Canvas};
+use boxcrash::svg;
+use boxcrash::{GameConfig, Pixel};
+
+use std::cell::RefCell;
+use std::io;
+use std::ops::DerefMut;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use cgmath::{vec2, Vector2};
+use piston_window::*;
+
+// `Game` draws a `Simulation` to a piston window and feeds it
+// with the window's input.
+pub struct Game {
+    sim: Simulation,
+    step: FixedStep,            // Turn real time into updates of `sim`
+    playback: Option<Playback>, // Replay being played, ignore player's input
+    fps: f64,                   // Real fps of game
+    last_frame: Instant,        // Moment of the last draw
+    // Wrap this cache in `RefCell` to allow interior mutability
+    glyphs: RefCell<Glyphs>, // Font cache
+}
+
+impl Game {
+    pub fn new(config: GameConfig, window: &PistonWindow) -> Game {
+        Game::with_simulation(Simulation::new(config), None, window)
+    }
+
+    // Play a replay, then give control to the player when it ends
+    pub fn from_replay(replay: Replay, window: &PistonWindow) -> Game {
+        let sim = Simulation::new(replay.config.clone());
+        Game::with_simulation(sim, Some(Playback::new(replay)), window)
+    }
+
+    // Continue a saved game
+    pub fn from_snapshot(snapshot: Snapshot, window: &PistonWindow) -> Game {
+        Game::with_simulation(Simulation::from_snapshot(snapshot), None, window)
+    }
+
+    fn with_simulation(sim: Simulation, playback: Option<Playback>, window: &PistonWindow) -> Game {
+        let glyphs = Glyphs::new(
+            FONT,
+            window.factory.clone(),
+            texture::TextureSettings::new(),
+        )
+        .expect("Unable to load font.");
+
+        let step = FixedStep::new(sim.config.ups, sim.config.max_catch_up);
+        // Print the seed so that the run can be reproduced
+        println!("Seed: {}", sim.seed());
+
+        Game {
+            sim,
+            step,
+            playback,
+            fps: 0.,
+            last_frame: Instant::now(),
+            glyphs: RefCell::new(glyphs),
+        }
+    }
+
+    // Re-calculate fps, return duration since the last frame
+    fn update_fps(&mut self) -> f64 {
+        let d = self.last_frame.elapsed();
+        self.last_frame = Instant::now();
+        let dt = d.as_secs() as f64 + 1e-9 * f64::from(d.subsec_nanos());
+        self.fps = 1. / dt;
+        dt
+    }
+
+    fn draw(&mut self, e: &Event, window: &mut PistonWindow) {
+        let frame = self.sim.frame(self.step.alpha());
+        let layers = self.sim.render_layers(&frame);
+        let hud = hud(&self.sim, &frame, Some(self.fps));
+        let size = self.sim.config.screen_size;
+        // Closure in `draw_2d` requires unique access to `self`,
+        // so we use RefCell to hack it.
+        let mut glyphs = self.glyphs.borrow_mut();
+        window.draw_2d(e, |c, g| {
+            clear(BLACK.into(), g);
+            for layer in &layers {
+                draw_list(&layer.primitives, size, glyphs.deref_mut(), c, g);
+            }
+            draw_list(&hud, size, glyphs.deref_mut(), c, g);
+        });
+    }
+
+    // Apply the replay's commands of the next update
+    fn play_back(&mut self) {
+        let tick = self.sim.ticks();
+        if let Some(playback) = &mut self.playback {
+            playback.apply(&mut self.sim, tick);
+            if playback.finished(tick) {
+                self.playback = None;
+            }
+        }
+    }
+
+    // Apply a reloaded config from the next update on, return the
+    // fields that can't change during a game. A replay being played
+    // keeps its own config.
+    pub fn reconfigure(&mut self, config: GameConfig) -> Vec<&'static str> {
+        if self.playback.is_some() {
+            println!("Playing a replay, the reloaded config will apply to the next game");
+            return Vec::new();
+        }
+        let max_catch_up = self.sim.config.max_catch_up;
+        let kept = self.sim.reconfigure(config);
+        // `ups` can't change during a game, the step keeps the
+        // simulation's
+        let config = &self.sim.config;
+        if config.max_catch_up != max_catch_up {
+            self.step = FixedStep::new(config.ups, config.max_catch_up);
+        }
+        kept
+    }
+
+    // Save a file named `<name>-<time>.<ext>` to `dir`, the file is
+    // written by `save`.
+    fn save<F>(dir: &str, name: &str, ext: &str, save: F)
+    where
+        F: FnOnce(&Path) -> io::Result<()>,
+    {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs())
+            .unwrap_or(0);
+        let path = PathBuf::from(dir).join(format!("{}-{}.{}", name, secs, ext));
+        match std::fs::create_dir_all(dir).and_then(|_| save(&path)) {
+            Ok(()) => println!("Saved {}", path.display()),
+            Err(e) => eprintln!("Cannot save {}: {}", path.display(), e),
+        }
+    }
+
+    // Follow the window's size, it may have changed while a menu was
+    // shown. A minimized window keeps the last size.
+    fn fit(&mut self, window: &PistonWindow) {
+        let size = window.size();
+        let size = Pixel::new(size.width, size.height);
+        if size != self.sim.config.screen_size && size.w > 0 && size.h > 0 {
+            self.sim.resize(size);
+        }
+    }
+
+    // Run the updates that fit in `dt` seconds of real time
+    fn update(&mut self, dt: f64) {
+        for _ in 0..self.step.advance(dt) {
+            self.play_back();
+            self.sim.step();
+            if self.sim.finished() {
+                break;
+            }
+        }
+    }
+}
+
+// Font size of the fps counter
+const FPS_SIZE: u32 = 10;
+const FONT: &str = "resources/Ubuntu-R.ttf";
+
+// Bounding square of the crosshair shown in stare mode
+fn crosshair(sim: &Simulation) -> [f64; 4] {
+    let size = sim.config.screen_size;
+    let w = 20.;
+    let x = f64::from(size.w) / 2. - w / 2.;
+    let y = f64::from(size.h) / 2. - w / 2.;
+    [x, y, w, w]
+}
+
+// Shapes drawn over the game: the readings, the fps counter if `fps`
+// is given, the minimap and the crosshair in stare mode.
+fn hud(sim: &Simulation, frame: &Frame, fps: Option<f64>) -> DisplayList {
+    let mut ret = sim.render_hud(frame);
+    if let Some(fps) = fps {
+        let size = f64::from(FPS_SIZE);
+        ret.push(Primitive::text(
+            vec2(0., size),
+            size,
+            &format!("{:.3}", fps),
+            WHITE,
+        ));
+    }
+    ret.extend(sim.render_minimap(frame));
+    if sim.state().rotate_cam {
+        let [x, y, w, _] = crosshair(sim);
+        let centre = vec2(x + w / 2., y + w / 2.);
+        ret.push(Primitive::circle(centre, w / 2., Some(2.), RED.alpha(0.5)));
+        ret.push(Primitive::rectangle(
+            [centre.x - 1., centre.y - 1., 2., 2.],
+            RED,
+        ));
+    }
+    ret
+}
+
+// Draw a display list with piston's graphics, dashes are cut on the
+// window of `size`.
+fn draw_list(list: &[Primitive], size: Pixel, glyphs: &mut Glyphs, c: Context, g: &mut G2d) {
+    let max = vec2(f64::from(size.w), f64::from(size.h));
+    for x in list {
+        let color: [f32; 4] = x.color.into();
+        match &x.shape {
+            Shape::Line {
+                points,
+                width,
+                stroke: Stroke::Solid,
+            } => line(color, width / 2., convert(*points), c.transform, g),
+            Shape::Line {
+                points,
+                width,
+                stroke: Stroke::Dashed { dash, gap },
+            } => {
+                for l in dashes(*points, *dash, *gap, vec2(0., 0.), max) {
+                    line(color, width / 2., convert(l), c.transform, g);
+                }
+            }
+            Shape::Polygon(points) => {
+                let points: Vec<_> = points.iter().map(|p| [p.x, p.y]).collect();
+                polygon(color, &points, c.transform, g);
+            }
+            Shape::Circle {
+                centre,
+                radius,
+                outline,
+            } => {
+                let rect = ellipse::circle(centre.x, centre.y, *radius);
+                match outline {
+                    Some(w) => {
+                        Ellipse::new_border(color, w / 2.).draw(rect, &c.draw_state, c.transform, g)
+                    }
+                    None => ellipse(color, rect, c.transform, g),
+                }
+            }
+            Shape::Text { pos, size, text: s } => {
+                text(
+                    color,
+                    *size as u32,
+                    s,
+                    glyphs,
+                    c.transform.trans(pos.x, pos.y),
+                    g,
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+// Draw a game with the software rasterizer and save it as PNG,
+// the fps counter is drawn if `fps` is given.
+pub fn screenshot(sim: &Simulation, alpha: f64, fps: Option<f64>, path: &Path) -> io::Result<()> {
+    let mut canvas = Canvas::new(sim.config.screen_size, BLACK);
+    let frame = sim.frame(alpha);
+    let hud = hud(sim, &frame, fps);
+    if hud.iter().any(|x| matches!(x.shape, Shape::Text { .. })) {
+        canvas.set_font(raster::load_font(FONT)?);
+    }
+    canvas.draw_layers(&sim.render_layers(&frame));
+    canvas.draw(&hud);
+    canvas.save_png(path)
+}
+
+// Save a frame of a game as SVG, grouped by layers
+pub fn save_svg(sim: &Simulation, alpha: f64, path: &Path) -> io::Result<()> {
+    let layers = sim.render_layers(&sim.frame(alpha));
+    svg::save(path, sim.config.screen_size, BLACK, &layers)
+}
+
+// Map a button to the player's control
+fn control(button: Button) -> Option<Control> {
+    match button {
+        Button::Keyboard(Key::A) => Some(Control::Left),
+        Button::Keyboard(Key::D) => Some(Control::Right),
+        Button::Keyboard(Key::W) => Some(Control::Sprint),
+        Button::Keyboard(Key::Space) => Some(Control::Jump),
+        Button::Keyboard(Key::C) => Some(Control::NextCamera),
+        Button::Mouse(MouseButton::Right) => Some(Control::Stare),
+        Button::Mouse(MouseButton::Left) => Some(Control::Shoot),
+        _ => None,
+    }
+}
+
+impl EventHandler for Game {
+    type Input = ();
+    fn handle_event(
+        &mut self,
+        e: Event,
+        window: &mut PistonWindow,
+        _: &mut Self::Input,
+    ) -> Option<Flow> {
+        use crate::Event::*;
+        use crate::Input::*;
+        use crate::Loop::*;
+        use crate::Motion::*;
+        use piston_window::Button::Keyboard;
+        self.fit(window);
+        match e {
+            Loop(Render(_)) => {
+                let dt = self.update_fps();
+                self.update(dt);
+                self.draw(&e, window);
+            }
+            Input(Button(args))
+                if args.button == Keyboard(Key::F2) && args.state == ButtonState::Press =>
+            {
+                Game::save("replays", "replay", "json", |x| self.sim.replay().save(x))
+            }
+            Input(Button(args))
+                if args.button == Keyboard(Key::F5) && args.state == ButtonState::Press =>
+            {
+                Game::save("saves", "snapshot", "json", |x| self.sim.snapshot().save(x))
+            }
+            Input(Button(args))
+                if args.button == Keyboard(Key::F11) && args.state == ButtonState::Press =>
+            {
+                let alpha = self.step.alpha();
+                Game::save("screenshots", "frame", "svg", |x| {
+                    save_svg(&self.sim, alpha, x)
+                })
+            }
+            Input(Button(args))
+                if args.button == Keyboard(Key::F12) && args.state == ButtonState::Press =>
+            {
+                let (alpha, fps) = (self.step.alpha(), Some(self.fps));
+                Game::save("screenshots", "screenshot", "png", |x| {
+                    screenshot(&self.sim, alpha, fps, x)
+                })
+            }
+            // Player's input is ignored while playing a replay
+            Input(_) if self.playback.is_some() => {}
+            Input(Button(args)) => {
+                use crate::ButtonState::*;
+                if let Some(x) = control(args.button) {
+                    self.sim.apply(match args.state {
+                        Press => Command::Press(x),
+                        Release => Command::Release(x),
+                    });
+                }
+            }
+            Input(Move(MouseRelative(a, b))) => self.sim.apply(Command::MouseMove(a, b)),
+            _ => {}
+        }
+
+        if self.sim.finished() {
+            Some(Flow::LoseGame)
+        } else {
+            None
+        }
+    }
+}
+
+fn convert(x: [Vector2<f64>; 2]) -> [f64; 4] {
+    [x[0].x, x[0].y, x[1].x, x[1].y]
+}
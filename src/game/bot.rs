@@ -1,12 +1,12 @@
-use super::camera::Camera;
 use super::car::*;
 use crate::color::*;
-use crate::rnd;
-use cgmath::{vec3, Vector2, Vector3};
+use crate::{rnd, GameRng};
+use cgmath::{vec3, Vector3};
+use rand::Rng;
 
 // Action with duration
 // e.g. `TurnLeft(3.)` means turn left for 3s
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Action {
     Rest(f64), // Do nothing
     Jump,
@@ -15,6 +15,7 @@ pub enum Action {
 }
 
 // Rules to generate new bot
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BoxRules {
     pub size: [(f64, f64); 3],
     pub position: [(f64, f64); 3],
@@ -24,18 +25,19 @@ pub struct BoxRules {
     pub jump_turn_decrease: f64,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Bot {
+    pub id: u64, // Identify the bot between updates
     pub car: BoxCar,
     pub actions: Vec<Action>,
 }
 
 impl Action {
-    fn rand() -> Action {
-        match ::rand::random::<usize>() % 4 {
-            0 => Action::Rest(rnd((0.25, 1.))),
-            1 => Action::TurnLeft(rnd((0.25, 1.))),
-            2 => Action::TurnRight(rnd((0.25, 1.))),
+    fn rand(rng: &mut GameRng) -> Action {
+        match rng.gen::<usize>() % 4 {
+            0 => Action::Rest(rnd(rng, (0.25, 1.))),
+            1 => Action::TurnLeft(rnd(rng, (0.25, 1.))),
+            2 => Action::TurnRight(rnd(rng, (0.25, 1.))),
             3 => Action::Jump,
             _ => panic!("Unexpected error in `Action::rand()`"),
         }
@@ -43,21 +45,26 @@ impl Action {
 }
 
 impl Bot {
-    pub fn new_random(rules: &BoxRules) -> Bot {
+    pub fn new_random(rules: &BoxRules, rng: &mut GameRng) -> Bot {
         Bot {
+            id: 0,
             car: BoxCar {
-                size: vec3(rnd(rules.size[0]), rnd(rules.size[1]), rnd(rules.size[2])),
+                size: vec3(
+                    rnd(rng, rules.size[0]),
+                    rnd(rng, rules.size[1]),
+                    rnd(rng, rules.size[2]),
+                ),
                 position: vec3(
-                    rnd(rules.position[0]),
-                    rnd(rules.position[1]),
-                    rnd(rules.position[2]),
+                    rnd(rng, rules.position[0]),
+                    rnd(rng, rules.position[1]),
+                    rnd(rng, rules.position[2]),
                 ),
-                speed: rnd(rules.speed),
-                turn_speed: rnd(rules.turn_speed),
+                speed: rnd(rng, rules.speed),
+                turn_speed: rnd(rng, rules.turn_speed),
                 color: if rules.color.is_empty() {
                     RED
                 } else {
-                    rules.color[::rand::random::<usize>() % rules.color.len()]
+                    rules.color[rng.gen::<usize>() % rules.color.len()]
                 },
                 jump_v: 5.,
                 jump_a: 7.,
@@ -65,8 +72,8 @@ impl Bot {
                 current_t: 0.,
                 jump_turn_decrease: rules.jump_turn_decrease,
             },
-            actions: (0..::rand::random::<usize>() % 6)
-                .map(|_| Action::rand())
+            actions: (0..rng.gen::<usize>() % 6)
+                .map(|_| Action::rand(rng))
                 .collect(),
         }
     }
@@ -98,9 +105,6 @@ impl Bot {
 
 // Bot is also a `Car`
 impl Car for Bot {
-    fn render(&self, cam: &Camera) -> Vec<([Vector2<f64>; 2], Color)> {
-        self.car.render(cam)
-    }
     fn crashed(&self, x: &Self) -> bool {
         self.car.crashed(&x.car)
     }
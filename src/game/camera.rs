@@ -1,10 +1,107 @@
 use cgmath::prelude::*;
 use cgmath::{vec3, Vector2, Vector3};
 
-const MAX_CAM_WIDTH: f64 = 1.0;
+// Distance from the camera to the screen, before zooming in
+const SCREEN_DISTANCE: f64 = 0.5;
+// Distance from the camera to the far plane, things farther away
+// are not rendered
+const FAR: f64 = 1000.;
+
+// Field of view in degrees, across the window's width or height
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fov {
+    Horizontal(f64),
+    Vertical(f64),
+}
+
+// How the camera projects the world to the window
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Projection {
+    Perspective(Fov),
+    Orthographic(f64), // Height of the view in world's units
+}
+
+impl Projection {
+    // Widen the field of view by `degrees`, an orthographic projection
+    // has none and is kept
+    pub fn widen(self, degrees: f64) -> Projection {
+        match self {
+            Projection::Perspective(Fov::Horizontal(x)) => {
+                Projection::Perspective(Fov::Horizontal(x + degrees))
+            }
+            Projection::Perspective(Fov::Vertical(x)) => {
+                Projection::Perspective(Fov::Vertical(x + degrees))
+            }
+            x => x,
+        }
+    }
+}
+
+// Views of the camera, switched in turn while playing
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CameraMode {
+    Chase,
+    FirstPerson,
+    TopDown,
+    Cinematic,
+}
+
+impl CameraMode {
+    pub fn next(self) -> CameraMode {
+        match self {
+            CameraMode::Chase => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::TopDown,
+            CameraMode::TopDown => CameraMode::Cinematic,
+            CameraMode::Cinematic => CameraMode::Chase,
+        }
+    }
+
+    // The player can stare and shoot only in the views that look
+    // down the tunel
+    pub fn can_aim(self) -> bool {
+        match self {
+            CameraMode::Chase | CameraMode::FirstPerson => true,
+            CameraMode::TopDown | CameraMode::Cinematic => false,
+        }
+    }
+}
+
+// Where the camera is and the point it looks at, from the player's
+// position
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct View {
+    pub eye: [f64; 3],
+    pub look: [f64; 3],
+}
+
+impl View {
+    // Move toward `other` by `t`, from 0 to 1
+    pub fn lerp(&self, other: &View, t: f64) -> View {
+        let lerp = |a: [f64; 3], b: [f64; 3]| {
+            let (a, b): (Vector3<f64>, Vector3<f64>) = (a.into(), b.into());
+            (a + t * (b - a)).into()
+        };
+        View {
+            eye: lerp(self.eye, other.eye),
+            look: lerp(self.look, other.look),
+        }
+    }
+}
+
+// Views of the modes other than the chase view, which is set by
+// the camera's height and distance
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraViews {
+    pub first_person: View,
+    pub top_down: View,
+    pub cinematic: View,
+}
 
 // A struct to render points and lines in 3D plane to the screen
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Camera {
     // Location of the camera
     pub eye: Vector3<f64>,
@@ -12,64 +109,182 @@ pub struct Camera {
     pub c: Vector3<f64>,
     axis_x: Vector3<f64>,
     axis_y: Vector3<f64>,
-    zoom_factor: f64,
+    projection: Projection,
     screen_size: crate::Pixel,
 }
 
 impl Camera {
-    pub fn new(size: crate::Pixel, location: Vector3<f64>) -> Camera {
+    pub fn new(size: crate::Pixel, location: Vector3<f64>, projection: Projection) -> Camera {
         Camera {
             eye: location,
-            c: vec3(0., 0., 0.5),
+            c: vec3(0., 0., SCREEN_DISTANCE),
             axis_x: vec3(1.0, 0.0, 0.0),
             axis_y: vec3(0.0, 1.0, 0.0),
-            zoom_factor: f64::from(size.w) / MAX_CAM_WIDTH,
+            projection,
             screen_size: size,
         }
     }
 
-    // Render a single point, return None if we can't see it
-    pub fn render(&self, x: &Vector3<f64>) -> Option<Vector2<f64>> {
-        let centre = self.eye + self.c;
-        let side = |x: &Vector3<f64>| self.c.dot(x - centre);
-        if side(x) * side(&self.eye) > 0. {
+    pub fn screen_size(&self) -> crate::Pixel {
+        self.screen_size
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    // Follow the window's new size, the field of view is kept
+    pub fn resize(&mut self, size: crate::Pixel) {
+        self.screen_size = size;
+    }
+
+    // Pixels per unit on the screen, zoomed in with the distance to
+    // the screen. In orthographic projection, pixels per world's unit.
+    fn focal(&self) -> f64 {
+        let (w, h) = (f64::from(self.screen_size.w), f64::from(self.screen_size.h));
+        let half = |x: f64| (x.to_radians() / 2.).tan();
+        let scale = match self.projection {
+            Projection::Perspective(Fov::Horizontal(x)) => w / 2. / half(x),
+            Projection::Perspective(Fov::Vertical(x)) => h / 2. / half(x),
+            Projection::Orthographic(x) => h / x,
+        };
+        scale * self.c.magnitude() / SCREEN_DISTANCE
+    }
+
+    // Position of `x` seen from the camera: to the right, up and
+    // forward.
+    fn view(&self, x: &Vector3<f64>) -> Vector3<f64> {
+        let d = x - self.eye;
+        vec3(
+            d.dot(self.axis_x) / self.axis_x.magnitude(),
+            d.dot(self.axis_y) / self.axis_y.magnitude(),
+            d.dot(self.c) / self.c.magnitude(),
+        )
+    }
+
+    // Planes of the camera's view in its own space, as `(n, k)`:
+    // a point `p` is in sight if `n.dot(p) + k >= 0` for each of them.
+    fn planes(&self) -> [(Vector3<f64>, f64); 6] {
+        let focal = self.focal();
+        let kx = f64::from(self.screen_size.w) / 2. / focal;
+        let ky = f64::from(self.screen_size.h) / 2. / focal;
+        let near = (vec3(0., 0., 1.), -self.c.magnitude()); // The screen
+        let far = (vec3(0., 0., -1.), FAR);
+        match self.projection {
+            // Sides widen with the distance
+            Projection::Perspective(_) => [
+                near,
+                far,
+                (vec3(1., 0., kx), 0.), // Left
+                (vec3(-1., 0., kx), 0.),
+                (vec3(0., 1., ky), 0.), // Bottom
+                (vec3(0., -1., ky), 0.),
+            ],
+            Projection::Orthographic(_) => [
+                near,
+                far,
+                (vec3(1., 0., 0.), kx),
+                (vec3(-1., 0., 0.), kx),
+                (vec3(0., 1., 0.), ky),
+                (vec3(0., -1., 0.), ky),
+            ],
+        }
+    }
+
+    // Where the camera's ray to `p` starts: the eye, or the point of
+    // the eye's plane in front of `p` in orthographic projection
+    pub fn ray_origin(&self, p: Vector3<f64>) -> Vector3<f64> {
+        match self.projection {
+            Projection::Perspective(_) => self.eye,
+            Projection::Orthographic(_) => p - self.view(&p).z * self.c.normalize(),
+        }
+    }
+
+    // Distance from the eye's plane to `p`
+    pub fn depth(&self, p: Vector3<f64>) -> f64 {
+        self.view(&p).z
+    }
+
+    // Project a point of the camera's space to the window, return
+    // None if it is behind the camera or not a number.
+    fn project(&self, v: Vector3<f64>) -> Option<Vector2<f64>> {
+        if v.z <= 0. {
+            return None;
+        }
+        let focal = self.focal();
+        let (x, y) = match self.projection {
+            Projection::Perspective(_) => (v.x / v.z, v.y / v.z),
+            Projection::Orthographic(_) => (v.x, v.y),
+        };
+        let w = focal * x + f64::from(self.screen_size.w) / 2.;
+        let h = f64::from(self.screen_size.h) / 2. - focal * y;
+        if w.is_finite() && h.is_finite() {
+            Some(Vector2::new(w, h))
+        } else {
             None
+        }
+    }
+
+    // Render a single point, return None if it is behind the screen.
+    // The point can be outside of the window.
+    pub fn render(&self, x: &Vector3<f64>) -> Option<Vector2<f64>> {
+        let v = self.view(x);
+        if v.z >= self.c.magnitude() {
+            self.project(v)
         } else {
-            let x = (self.c.magnitude2() / self.c.dot(x - self.eye)) * (x - self.eye) - self.c;
-            Some(self.transform(&x))
+            None
         }
     }
 
+    // Render a line clipped to the camera's view, return None if no
+    // part of it can be seen.
     pub fn render_line(&self, x: &Vector3<f64>, y: &Vector3<f64>) -> Option<[Vector2<f64>; 2]> {
-        let centre = self.eye + self.c;
-        let (x1, y1) = (self.render(x), self.render(y));
-        if x1.is_some() && y1.is_some() {
-            Some([x1.unwrap(), y1.unwrap()])
-        } else if x1.is_none() && y1.is_none() {
-            None
-        } else {
-            let (a, v) = if x1.is_some() { (x, y - x) } else { (y, x - y) };
-            let t = self.c.dot(centre - a) / self.c.dot(v);
-            let x = t * v + a - centre;
-            Some([x1.unwrap_or_else(|| y1.unwrap()), self.transform(&x)])
+        let (a, b) = (self.view(x), self.view(y));
+        let d = b - a;
+        let (mut t0, mut t1) = (0f64, 1f64);
+        for &(n, k) in &self.planes() {
+            let (side, speed) = (n.dot(a) + k, n.dot(d));
+            if speed == 0. {
+                if side < 0. {
+                    return None;
+                }
+            } else {
+                let t = -side / speed;
+                if speed > 0. {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        if t0 > t1 {
+            return None;
         }
+        Some([self.project(a + t0 * d)?, self.project(a + t1 * d)?])
     }
 
-    fn transform(&self, x: &Vector3<f64>) -> Vector2<f64> {
-        if f64::abs(self.c.dot(*x)) > 1e-9 {
-            panic!(
-                "Unexpected error in `fn Camera::transform`: {:?} {:?}, {}",
-                self.c,
-                x,
-                self.c.dot(x.clone())
-            );
+    // Render a convex polygon clipped to the camera's view, return
+    // None if less than a triangle is left.
+    pub fn render_polygon(&self, points: &[Vector3<f64>]) -> Option<Vec<Vector2<f64>>> {
+        let mut polygon: Vec<_> = points.iter().map(|x| self.view(x)).collect();
+        for &(n, k) in &self.planes() {
+            let side = |p: &Vector3<f64>| n.dot(*p) + k;
+            let mut next = Vec::new();
+            for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+                let (sa, sb) = (side(a), side(b));
+                if sa >= 0. {
+                    next.push(*a);
+                }
+                if (sa >= 0.) != (sb >= 0.) {
+                    next.push(a + sa / (sa - sb) * (b - a));
+                }
+            }
+            polygon = next;
         }
-        let a = x.dot(self.axis_x) / self.axis_x.magnitude();
-        let b = x.dot(self.axis_y) / self.axis_y.magnitude();
-        let w = a * self.zoom_factor + f64::from(self.screen_size.w) / 2.;
-        let h = f64::from(self.screen_size.h)
-            - (b * self.zoom_factor + f64::from(self.screen_size.h) / 2.);
-        Vector2::new(w, h)
+        if polygon.len() < 3 {
+            return None;
+        }
+        polygon.into_iter().map(|x| self.project(x)).collect()
     }
 
     // Rotate the camera's direction around a centre and
@@ -80,13 +295,36 @@ impl Camera {
         let mut vec = self.eye - centre;
         let rotate_x = Basis3::from_angle_x(Rad(x));
         let rotate_y = Basis3::from_angle_y(Rad(y));
-        for v in vec![&mut self.c, &mut self.axis_x, &mut self.axis_y, &mut vec] {
+        for v in [&mut self.c, &mut self.axis_x, &mut self.axis_y, &mut vec] {
             *v = rotate_x.rotate_vector(*v);
             *v = rotate_y.rotate_vector(*v);
         }
         self.eye = centre + vec;
     }
 
+    // Move the camera to `eye` and turn it toward `target`, keeping
+    // the sky up, or the tunel's far end when looking straight down
+    pub fn look_at(&mut self, eye: Vector3<f64>, target: Vector3<f64>) {
+        self.eye = eye;
+        if target == eye {
+            return;
+        }
+        let c = (target - eye).normalize();
+        let mut axis_x = vec3(0., 1., 0.).cross(c);
+        if axis_x.magnitude2() < 1e-12 {
+            axis_x = vec3(0., 0., 1.).cross(c);
+        }
+        self.axis_x = axis_x.normalize();
+        self.axis_y = c.cross(self.axis_x);
+        self.c = c * SCREEN_DISTANCE;
+    }
+
+    // Place the camera at `view` from `position`
+    pub fn follow(&mut self, position: Vector3<f64>, view: &View) {
+        let (eye, look): (Vector3<f64>, Vector3<f64>) = (view.eye.into(), view.look.into());
+        self.look_at(position + eye, position + look);
+    }
+
     pub fn zoom_in(&mut self) {
         self.c *= 2.;
     }
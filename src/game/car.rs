@@ -1,12 +1,12 @@
-use super::camera::Camera;
+use super::hidden::Occluder;
+use super::solid::Face;
 use crate::color::*;
-use cgmath::{Vector2, Vector3};
+use cgmath::{vec3, Vector3};
 
 // Present a car that can be drawed, check for collision
 // with other car and bullet, turn left/right, move forward
 // and jump.
 pub trait Car {
-    fn render(&self, _: &Camera) -> Vec<([Vector2<f64>; 2], Color)>;
     fn crashed(&self, _: &Self) -> bool;
     fn hit(&self, _: &[Vector3<f64>; 3]) -> bool;
     fn forward(&mut self, dt: f64, outside_speed: f64);
@@ -19,7 +19,7 @@ pub trait Car {
 }
 
 // Car with shape of a box
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BoxCar {
     pub size: Vector3<f64>,
     pub position: Vector3<f64>,
@@ -33,8 +33,9 @@ pub struct BoxCar {
     pub jump_turn_decrease: f64,
 }
 
-impl Car for BoxCar {
-    fn render(&self, camera: &Camera) -> Vec<([Vector2<f64>; 2], Color)> {
+impl BoxCar {
+    // The box's 12 edges
+    pub fn edges(&self) -> Vec<[Vector3<f64>; 2]> {
         let mut front = [self.position; 4];
         front[0].y += self.size.y;
         front[1].y += self.size.y;
@@ -48,15 +49,89 @@ impl Car for BoxCar {
         }
         let mut ret = Vec::new();
         for i in 0..4 {
-            ret.push(camera.render_line(&front[i], &front[(i + 1) % 4]));
-            ret.push(camera.render_line(&rear[i], &rear[(i + 1) % 4]));
-            ret.push(camera.render_line(&front[i], &rear[i]));
+            ret.push([front[i], front[(i + 1) % 4]]);
+            ret.push([rear[i], rear[(i + 1) % 4]]);
+            ret.push([front[i], rear[i]]);
+        }
+        ret
+    }
+
+    // The box's 6 faces
+    pub fn faces(&self) -> Vec<Face> {
+        let half = vec3(self.size.x / 2., 0., 0.);
+        let (a, b) = (
+            self.position - half,
+            self.position + half + vec3(0., self.size.y, self.size.z),
+        );
+        vec![
+            Face::new(
+                [
+                    vec3(a.x, a.y, a.z),
+                    vec3(b.x, a.y, a.z),
+                    vec3(b.x, b.y, a.z),
+                    vec3(a.x, b.y, a.z),
+                ],
+                vec3(0., 0., -1.),
+            ),
+            Face::new(
+                [
+                    vec3(a.x, a.y, b.z),
+                    vec3(a.x, b.y, b.z),
+                    vec3(b.x, b.y, b.z),
+                    vec3(b.x, a.y, b.z),
+                ],
+                vec3(0., 0., 1.),
+            ),
+            Face::new(
+                [
+                    vec3(a.x, a.y, a.z),
+                    vec3(a.x, b.y, a.z),
+                    vec3(a.x, b.y, b.z),
+                    vec3(a.x, a.y, b.z),
+                ],
+                vec3(-1., 0., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(b.x, a.y, a.z),
+                    vec3(b.x, a.y, b.z),
+                    vec3(b.x, b.y, b.z),
+                    vec3(b.x, b.y, a.z),
+                ],
+                vec3(1., 0., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(a.x, a.y, a.z),
+                    vec3(a.x, a.y, b.z),
+                    vec3(b.x, a.y, b.z),
+                    vec3(b.x, a.y, a.z),
+                ],
+                vec3(0., -1., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(a.x, b.y, a.z),
+                    vec3(b.x, b.y, a.z),
+                    vec3(b.x, b.y, b.z),
+                    vec3(a.x, b.y, b.z),
+                ],
+                vec3(0., 1., 0.),
+            ),
+        ]
+    }
+
+    // The space taken by the box, it hides lines behind it
+    pub fn occluder(&self) -> Occluder {
+        let half = vec3(self.size.x / 2., 0., 0.);
+        Occluder {
+            min: self.position - half,
+            max: self.position + half + vec3(0., self.size.y, self.size.z),
         }
-        ret.into_iter()
-            .filter_map(|x| x.map(|x| (x, self.color)))
-            .collect()
     }
+}
 
+impl Car for BoxCar {
     fn turn_left(&mut self, dt: f64) {
         self.position.x -= dt * self.turn_speed();
     }
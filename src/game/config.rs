@@ -0,0 +1,464 @@
+use super::{
+    CameraMode, CameraViews, Corner, Fog, Fov, HiddenLines, Hud, Minimap, Projection, Shake,
+    SpeedLines, View,
+};
+use serde_json::Value;
+use std::fmt;
+
+// Configurable game's contansts.
+// A tuple presents a range of something.
+// Missing fields are read from `GameConfig::default()`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub title: String,
+    pub screen_size: crate::Pixel,
+    pub ups: u64,          // Update per second
+    pub max_catch_up: u32, // Max number of updates to catch up in one frame
+    pub max_fps: u64,
+    pub tunel_size: [f64; 3],
+    pub player_size: [f64; 3],
+    pub player_speed: (f64, f64), // min and max player speed
+    pub player_turn_speed: f64,
+    pub bot_size: [(f64, f64); 3], // Range of bot's size
+    pub bot_speed: (f64, f64),
+    pub bot_turn_speed: (f64, f64),
+    pub divider_size: [f64; 2],
+    pub camera_height: f64,        // Height of camera (from player)
+    pub camera_distance: f64,      // Distance from camera to player
+    pub camera_views: CameraViews, // Views of the other camera modes
+    pub camera_ease: f64,          // Time to ease from a camera mode to the next
+    pub camera_spring: f64,        // Stiffness of the camera's follow, no lag if 0
+    pub camera_shake: Shake,       // Shaking of the camera
+    pub fov: Fov,                  // Field of view in degrees
+    // Height of the view in world's units in orthographic projection,
+    // perspective projection if not set
+    pub orthographic_height: Option<f64>,
+    pub decor_distance: f64, // Distance between each decoration
+    pub sprint_factor: f64,
+    pub spawn_time: (f64, f64),
+    pub game_sprint: f64, // The increase of game_speed
+    pub game_max_speed: f64,
+    pub player_jump_v: f64,
+    pub player_jump_a: f64,
+    pub jump_turn_decrease: f64,
+    pub jump_timeout: f64,
+    pub mouse_speed: f64,
+    pub trueshot_distance: f64,
+    pub bullet_stock: i64, // Number of bullets
+    pub recharge_time: f64,
+    pub bullet_len: f64,
+    pub bullet_speed: f64,
+    pub debris_time: f64, // Time for debris of destroyed boxes to fade out
+    pub zoom_in: bool,    // If true, zoom-in while on stare mode
+    pub hidden_lines: HiddenLines, // Draw lines behind boxes, hide or dash them
+    pub solid_faces: bool, // Fill faces of boxes and the tunel
+    pub wireframe_overlay: bool, // Draw edges over solid faces
+    pub fog: Option<Fog>, // Fade things with their distance, no fog if not set
+    pub speed_lines: Option<SpeedLines>, // Streaks on the walls, none if not set
+    pub sprint_fov: f64,  // Degrees added to the field of view at top speed
+    pub minimap: Option<Minimap>, // Map of the tunel from above, none if not set
+    pub hud: Option<Hud>, // Panel of the game's readings, none if not set
+    pub seed: Option<u64>, // Seed of random generator, random if not set
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        use std::f64::consts::PI;
+        GameConfig {
+            title: "Box Crash".to_owned(),
+            screen_size: crate::Pixel::new(800, 600),
+            ups: 60,
+            max_catch_up: 5,
+            max_fps: 60,
+            tunel_size: [15., 8., 150.],
+            player_size: [1.5, 0.8, 3.],
+            player_speed: (20., 120.),
+            player_turn_speed: 15.,
+            bot_size: [(1., 4.), (0.5, 2.5), (2.5, 8.)],
+            bot_speed: (20., 120.),
+            bot_turn_speed: (5., 20.),
+            divider_size: [1., 7.],
+            camera_height: 3.,
+            camera_distance: 5.5,
+            camera_views: CameraViews {
+                first_person: View {
+                    eye: [0., 1.2, 3.2],
+                    look: [0., 1., 30.],
+                },
+                top_down: View {
+                    eye: [0., 30., -4.],
+                    look: [0., 0., 14.],
+                },
+                cinematic: View {
+                    eye: [6., 2., -2.],
+                    look: [0., 0.5, 4.],
+                },
+            },
+            camera_ease: 0.5,
+            camera_spring: 600.,
+            camera_shake: Shake {
+                near_miss: 0.1,
+                landing: 0.15,
+                crash: 0.6,
+            },
+            fov: Fov::Horizontal(90.),
+            orthographic_height: None,
+            decor_distance: 8.,
+            sprint_factor: 15.,
+            spawn_time: (0.25, 1.),
+            game_sprint: 1.,
+            game_max_speed: 80.,
+            player_jump_v: 7.,
+            player_jump_a: 5.,
+            jump_turn_decrease: 3.,
+            jump_timeout: 8.,
+            mouse_speed: PI / 420.,
+            trueshot_distance: 100.,
+            bullet_stock: 15,
+            recharge_time: 10.,
+            bullet_len: 5.,
+            bullet_speed: 100.,
+            debris_time: 1.5,
+            zoom_in: false,
+            hidden_lines: HiddenLines::Show,
+            solid_faces: false,
+            wireframe_overlay: true,
+            fog: Some(Fog {
+                color: crate::color::BLACK,
+                start: 20.,
+                end: 180.,
+            }),
+            speed_lines: Some(SpeedLines {
+                start: 40.,
+                density: 0.5,
+                trail: 0.05,
+            }),
+            sprint_fov: 10.,
+            minimap: Some(Minimap {
+                corner: Corner::TopRight,
+                scale: 1.5,
+                opacity: 0.6,
+            }),
+            hud: Some(Hud {
+                corner: Corner::BottomLeft,
+                text_size: 0.025,
+                opacity: 0.8,
+            }),
+            seed: None,
+        }
+    }
+}
+
+// An error in a config file
+#[derive(Debug)]
+pub enum ConfigError {
+    // Not a valid JSON, or a field is unknown, missing or has
+    // a wrong type
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    // A field has a value that the game can't run with
+    Invalid {
+        field: &'static str,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            ConfigError::Invalid {
+                field,
+                line: Some(line),
+                message,
+            } => write!(f, "line {}: `{}` {}", line, field, message),
+            ConfigError::Invalid {
+                field,
+                line: None,
+                message,
+            } => write!(f, "`{}` {}", field, message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> ConfigError {
+        // Location is already in `line` and `column`
+        let message = e.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_owned(),
+            None => message,
+        };
+        ConfigError::Parse {
+            line: e.line(),
+            column: e.column(),
+            message,
+        }
+    }
+}
+
+// Return the line where `field` is set in a JSON source
+pub(super) fn field_line(source: &str, field: &str) -> Option<usize> {
+    let key = format!("\"{}\"", field);
+    source
+        .lines()
+        .position(|x| match x.find(&key) {
+            Some(i) => x[i + key.len()..].trim_start().starts_with(':'),
+            None => false,
+        })
+        .map(|x| x + 1)
+}
+
+impl GameConfig {
+    // Check for values that the game can't run with, `source` is
+    // the JSON the config was read from, to find each field's line.
+    pub fn validate(&self, source: Option<&str>) -> Result<(), Vec<ConfigError>> {
+        let errors: Vec<_> = self
+            .check()
+            .into_iter()
+            .map(|(field, message)| ConfigError::Invalid {
+                field,
+                line: source.and_then(|x| field_line(x, field)),
+                message,
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Return each field that the game can't run with, and what is
+    // wrong with it.
+    pub(super) fn check(&self) -> Vec<(&'static str, String)> {
+        let mut errors = Vec::new();
+        {
+            let mut error = |field: &'static str, message: String| errors.push((field, message));
+            let mut range = |field: &'static str, (min, max): (f64, f64)| {
+                if min > max {
+                    error(field, format!("is an inverted range, {} > {}", min, max));
+                }
+            };
+            range("player_speed", self.player_speed);
+            range("bot_speed", self.bot_speed);
+            range("bot_turn_speed", self.bot_turn_speed);
+            range("spawn_time", self.spawn_time);
+            for &x in &self.bot_size {
+                range("bot_size", x);
+            }
+            if self.tunel_size.iter().any(|&x| x <= 0.) {
+                error("tunel_size", "must be positive".to_owned());
+            }
+            if self.screen_size.w == 0 || self.screen_size.h == 0 {
+                error("screen_size", "must be positive".to_owned());
+            }
+            // Dividers and decorations are repeated along the tunel
+            // by these lengths
+            if self.divider_size.iter().any(|&x| x <= 0.) {
+                error("divider_size", "must be positive".to_owned());
+            }
+            if self.decor_distance <= 0. {
+                error("decor_distance", "must be positive".to_owned());
+            }
+            if self.player_size[0] > self.tunel_size[0] {
+                error(
+                    "player_size",
+                    format!(
+                        "is wider than the tunel, {} > {}",
+                        self.player_size[0], self.tunel_size[0]
+                    ),
+                );
+            }
+            if self.bullet_stock <= 0 {
+                error("bullet_stock", "must be positive".to_owned());
+            }
+            // The HUD's gauges are parts of these
+            if self.recharge_time <= 0. {
+                error("recharge_time", "must be positive".to_owned());
+            }
+            if self.jump_timeout <= 0. {
+                error("jump_timeout", "must be positive".to_owned());
+            }
+            if self.debris_time < 0. {
+                error("debris_time", "must not be negative".to_owned());
+            }
+            if self.ups == 0 {
+                error("ups", "must be positive".to_owned());
+            }
+            if self.max_catch_up == 0 {
+                error("max_catch_up", "must be positive".to_owned());
+            }
+            match self.fov {
+                Fov::Horizontal(x) | Fov::Vertical(x) if x <= 0. || x >= 180. => error(
+                    "fov",
+                    format!("must be between 0 and 180 degrees, got {}", x),
+                ),
+                _ => (),
+            }
+            match self.fov {
+                Fov::Horizontal(x) | Fov::Vertical(x) if x + self.sprint_fov >= 180. => error(
+                    "sprint_fov",
+                    format!(
+                        "widens the field of view to 180 degrees or more, {} + {}",
+                        x, self.sprint_fov
+                    ),
+                ),
+                _ => (),
+            }
+            let views = &self.camera_views;
+            if [views.first_person, views.top_down, views.cinematic]
+                .iter()
+                .any(|x| x.eye == x.look)
+            {
+                error(
+                    "camera_views",
+                    "must not look at the camera's own place".to_owned(),
+                );
+            }
+            if self.camera_ease < 0. {
+                error("camera_ease", "must not be negative".to_owned());
+            }
+            if self.camera_spring < 0. {
+                error("camera_spring", "must not be negative".to_owned());
+            }
+            let shake = &self.camera_shake;
+            if [shake.near_miss, shake.landing, shake.crash]
+                .iter()
+                .any(|&x| x < 0.)
+            {
+                error("camera_shake", "must not be negative".to_owned());
+            }
+            if self.sprint_fov < 0. {
+                error("sprint_fov", "must not be negative".to_owned());
+            }
+            if let Some(x) = self.orthographic_height {
+                if x <= 0. {
+                    error("orthographic_height", "must be positive".to_owned());
+                }
+            }
+            if let Some(fog) = self.fog {
+                if fog.start < 0. || fog.start >= fog.end {
+                    error(
+                        "fog",
+                        format!(
+                            "must start at 0 or farther and before its end, got {} to {}",
+                            fog.start, fog.end
+                        ),
+                    );
+                }
+            }
+            if let Some(x) = self.minimap {
+                if x.scale <= 0. {
+                    error(
+                        "minimap",
+                        format!("must have a positive scale, got {}", x.scale),
+                    );
+                }
+                if x.opacity < 0. || x.opacity > 1. {
+                    error(
+                        "minimap",
+                        format!("must have an opacity from 0 to 1, got {}", x.opacity),
+                    );
+                }
+            }
+            if let Some(x) = self.hud {
+                if x.text_size <= 0. {
+                    error(
+                        "hud",
+                        format!("must have a positive text size, got {}", x.text_size),
+                    );
+                }
+                if x.opacity < 0. || x.opacity > 1. {
+                    error(
+                        "hud",
+                        format!("must have an opacity from 0 to 1, got {}", x.opacity),
+                    );
+                }
+            }
+            if let Some(x) = self.speed_lines {
+                if x.density < 0. || x.trail < 0. {
+                    error(
+                        "speed_lines",
+                        "must not have a negative density or trail".to_owned(),
+                    );
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn projection(&self) -> Projection {
+        match self.orthographic_height {
+            Some(x) => Projection::Orthographic(x),
+            None => Projection::Perspective(self.fov),
+        }
+    }
+
+    // The camera's view in `mode`
+    pub fn view(&self, mode: CameraMode) -> View {
+        let eye = [0., self.camera_height, -self.camera_distance];
+        match mode {
+            // Straight ahead, far enough to ease smoothly to the others
+            CameraMode::Chase => View {
+                eye,
+                look: [eye[0], eye[1], eye[2] + 100.],
+            },
+            CameraMode::FirstPerson => self.camera_views.first_person,
+            CameraMode::TopDown => self.camera_views.top_down,
+            CameraMode::Cinematic => self.camera_views.cinematic,
+        }
+    }
+
+    // Set the fields that can't change during a game back to their
+    // values in `old`, return the ones that were changed.
+    pub fn keep_restart_fields(&mut self, old: &GameConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        macro_rules! keep {
+            ($($field:ident),*) => {
+                $(
+                    if self.$field != old.$field {
+                        changed.push(stringify!($field));
+                        self.$field = old.$field.clone();
+                    }
+                )*
+            };
+        }
+        keep!(title, ups, tunel_size, player_size, seed);
+        changed
+    }
+
+    // Return every field's name with its value in JSON
+    pub fn fields(&self) -> Vec<(String, String)> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(map)) => map.into_iter().map(|(k, v)| (k, v.to_string())).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Set a field of a config in JSON by its name, nested fields are
+// separated by dots, e.g. `screen_size.w`. `value` is read as JSON,
+// a value with commas is read as a list (e.g. `30,90`) and anything
+// else as a string.
+pub(super) fn set_value(root: &mut Value, field: &str, value: &str) -> Result<(), String> {
+    let mut target = root;
+    for key in field.split('.') {
+        target = target
+            .get_mut(key)
+            .ok_or_else(|| format!("Unknown config field `{}`", field))?;
+    }
+    *target = serde_json::from_str(value)
+        .or_else(|_| serde_json::from_str(&format!("[{}]", value)))
+        .unwrap_or_else(|_| Value::String(value.to_owned()));
+    Ok(())
+}
@@ -0,0 +1,124 @@
+// Debris of destroyed boxes: their edges fly apart, spin, fall and
+// bounce off the tunel while they fade out.
+use super::car::BoxCar;
+use crate::color::Color;
+use cgmath::prelude::*;
+use cgmath::{vec3, Basis3, Rad, Vector3};
+
+const GRAVITY: f64 = 20.;
+// Speed of pieces away from the box's centre, and upward
+const BLAST: f64 = 6.;
+const LIFT: f64 = 5.;
+// Spin of pieces, radians per second
+const SPIN: f64 = 8.;
+// Part of the speed kept when bouncing, across and along the surface
+const BOUNCE: f64 = 0.5;
+const FRICTION: f64 = 0.7;
+
+// An edge of a destroyed box
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Piece {
+    pub centre: Vector3<f64>,
+    pub half: Vector3<f64>, // From the centre to an end of the edge
+    pub velocity: Vector3<f64>,
+    pub axis: Vector3<f64>, // Axis of spin
+    pub spin: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Debris {
+    pub id: u64, // Identify the debris between updates
+    pub pieces: Vec<Piece>,
+    pub color: Color,
+    pub age: f64, // Time since the box was destroyed
+}
+
+impl Piece {
+    pub fn line(&self) -> [Vector3<f64>; 2] {
+        [self.centre - self.half, self.centre + self.half]
+    }
+
+    // Move the piece, `speed` is the speed of the world toward the
+    // camera. Pieces bounce off the tunel of `size`.
+    fn update(&mut self, dt: f64, speed: f64, size: Vector3<f64>) {
+        self.velocity.y -= GRAVITY * dt;
+        self.centre += self.velocity * dt;
+        self.centre.z -= speed * dt;
+        let rotation = Basis3::from_axis_angle(self.axis, Rad(self.spin * dt));
+        self.half = rotation.rotate_vector(self.half);
+        // Keep both ends inside the tunel's walls, road and ceiling
+        for i in 0..2 {
+            let reach = self.half[i].abs();
+            let (min, max) = (reach, size[i] - reach);
+            let inward = if self.centre[i] < min {
+                self.centre[i] = min;
+                1.
+            } else if self.centre[i] > max {
+                self.centre[i] = max;
+                -1.
+            } else {
+                continue;
+            };
+            if self.velocity[i] * inward < 0. {
+                self.velocity[i] *= -BOUNCE;
+                let j = if i == 0 { 1 } else { 0 };
+                self.velocity[j] *= FRICTION;
+                self.velocity.z *= FRICTION;
+                self.spin *= FRICTION;
+            }
+        }
+    }
+}
+
+impl Debris {
+    // Break a box into its edges, which fly away from its centre.
+    // `velocity` is the box's own velocity, kept by the pieces.
+    pub fn new(id: u64, car: &BoxCar, velocity: Vector3<f64>) -> Debris {
+        let centre = car.position + vec3(0., car.size.y, car.size.z) / 2.;
+        let pieces = car
+            .edges()
+            .into_iter()
+            .enumerate()
+            .map(|(i, [a, b])| {
+                let middle = (a + b) / 2.;
+                let out = (middle - centre).normalize();
+                // Vary the pieces without using the game's random
+                // generator, so replays stay the same
+                let k = 1. + (i % 3) as f64 / 2.;
+                let sign = if i % 2 == 0 { 1. } else { -1. };
+                Piece {
+                    centre: middle,
+                    half: (b - a) / 2.,
+                    velocity: velocity + out * BLAST * k + vec3(0., LIFT, 0.),
+                    axis: (b - a).cross(out).normalize(),
+                    spin: SPIN * k * sign,
+                }
+            })
+            .collect();
+        Debris {
+            id,
+            pieces,
+            color: car.color,
+            age: 0.,
+        }
+    }
+
+    pub fn update(&mut self, dt: f64, speed: f64, size: Vector3<f64>) {
+        self.age += dt;
+        for x in &mut self.pieces {
+            x.update(dt, speed, size);
+        }
+    }
+
+    // Return the debris between `prev` and `self`, `alpha` is the
+    // progress from `prev` to `self`
+    pub fn interpolate(&self, prev: &Debris, alpha: f64) -> Debris {
+        let mut debris = self.clone();
+        debris.age = prev.age + alpha * (self.age - prev.age);
+        for (x, old) in debris.pieces.iter_mut().zip(&prev.pieces) {
+            x.centre = old.centre + alpha * (x.centre - old.centre);
+            x.half = old.half + alpha * (x.half - old.half);
+        }
+        debris
+    }
+}
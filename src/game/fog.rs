@@ -0,0 +1,89 @@
+// Distance fog: lines and faces fade into the fog's color with their
+// distance from the camera.
+use super::camera::Camera;
+use super::solid::Face;
+use crate::color::Color;
+use cgmath::Vector3;
+
+// Lines and faces are cut in pieces so that the fog's color changes
+// in about this many steps from its start to its end
+const STEPS: f64 = 16.;
+// Most pieces of a single line or face
+const MAX_PIECES: usize = 64;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fog {
+    pub color: Color,
+    pub start: f64, // Distance where things start to fade
+    pub end: f64,   // Distance where only the fog's color is left
+}
+
+impl Fog {
+    // Return `color` seen through the fog at `depth`
+    pub fn apply(&self, color: Color, depth: f64) -> Color {
+        let t = ((depth - self.start) / (self.end - self.start)).clamp(0., 1.);
+        color.mix(self.color, t as f32)
+    }
+
+    // Number of pieces to cut something between depths `a` and `b`
+    fn pieces(&self, a: f64, b: f64) -> usize {
+        let (near, far) = (a.min(b), a.max(b));
+        // Outside of the fog's range, the color doesn't change
+        if far <= self.start || near >= self.end {
+            return 1;
+        }
+        let step = (self.end - self.start) / STEPS;
+        ((((far - near) / step).ceil()) as usize).clamp(1, MAX_PIECES)
+    }
+
+    // Cut lines in pieces, each colored by the depth of its middle
+    pub fn lines(
+        &self,
+        lines: &[([Vector3<f64>; 2], Color)],
+        camera: &Camera,
+    ) -> Vec<([Vector3<f64>; 2], Color)> {
+        let mut ret = Vec::new();
+        for &([a, b], color) in lines {
+            let n = self.pieces(camera.depth(a), camera.depth(b));
+            let at = |i: usize| a + (b - a) * (i as f64 / n as f64);
+            for i in 0..n {
+                let (x, y) = (at(i), at(i + 1));
+                let depth = camera.depth((x + y) / 2.);
+                ret.push(([x, y], self.apply(color, depth)));
+            }
+        }
+        ret
+    }
+
+    // Cut a face in strips across the direction its depth changes
+    // the most
+    pub fn cut(&self, face: &Face, camera: &Camera) -> Vec<Face> {
+        let p = face.points;
+        let depth = |i: usize, j: usize| (camera.depth(p[i]) - camera.depth(p[j])).abs();
+        // Strips go from the edge `p[0]`, `p[3]` to `p[1]`, `p[2]`, or
+        // from `p[1]`, `p[0]` to `p[2]`, `p[3]`
+        let p = if depth(0, 1).max(depth(3, 2)) >= depth(1, 2).max(depth(0, 3)) {
+            p
+        } else {
+            [p[1], p[2], p[3], p[0]]
+        };
+        let d = p.iter().map(|&x| camera.depth(x));
+        let (near, far) = d.fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), x| {
+            (a.min(x), b.max(x))
+        });
+        let n = self.pieces(near, far);
+        let lerp = |a: Vector3<f64>, b: Vector3<f64>, i: usize| a + (b - a) * (i as f64 / n as f64);
+        (0..n)
+            .map(|i| {
+                let points = [
+                    lerp(p[0], p[1], i),
+                    lerp(p[0], p[1], i + 1),
+                    lerp(p[3], p[2], i + 1),
+                    lerp(p[3], p[2], i),
+                ];
+                Face::new(points, face.normal)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,90 @@
+// How the camera follows the player: a damped spring that lags a bit
+// behind its place, and shaking on near misses, landings and crashes.
+use cgmath::{vec3, Vector3};
+
+// Bots passing the player closer than this aside are near misses
+pub const NEAR_MISS: f64 = 1.;
+// How fast shaking calms down, its strength is divided by `e` in
+// `1 / SHAKE_DECAY` seconds
+const SHAKE_DECAY: f64 = 6.;
+// Frequencies of shaking across and up, in radians per second
+const SHAKE_X: f64 = 53.;
+const SHAKE_Y: f64 = 71.;
+
+// Strength of shaking for each event, in world's units, 0 for none
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Shake {
+    pub near_miss: f64,
+    pub landing: f64,
+    pub crash: f64,
+}
+
+// Move `offset` from the spring's rest by `dt` with its `velocity`,
+// critically damped so it settles without bouncing. Return the new
+// offset and velocity.
+pub fn spring(
+    offset: Vector3<f64>,
+    velocity: Vector3<f64>,
+    stiffness: f64,
+    dt: f64,
+) -> (Vector3<f64>, Vector3<f64>) {
+    // The exact solution keeps it steady with any stiffness
+    let w = stiffness.sqrt();
+    let k = (velocity + w * offset) * dt;
+    let decay = (-w * dt).exp();
+    ((offset + k) * decay, (velocity - w * k) * decay)
+}
+
+// Offset of the camera shaking with `strength` at `time`
+pub fn shake(strength: f64, time: f64) -> Vector3<f64> {
+    vec3(
+        strength * (SHAKE_X * time).sin(),
+        strength * (SHAKE_Y * time).sin(),
+        0.,
+    )
+}
+
+// Strength of shaking `dt` after it was `strength`
+pub fn calm(strength: f64, dt: f64) -> f64 {
+    strength * (-SHAKE_DECAY * dt).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::prelude::*;
+
+    #[test]
+    fn spring_settles_without_bouncing() {
+        let (mut offset, mut velocity) = (vec3(1., -2., 0.5), vec3(0., 0., 0.));
+        let start = offset;
+        for _ in 0..120 {
+            let (x, v) = spring(offset, velocity, 600., 1. / 60.);
+            // Critically damped: it never passes its rest
+            assert!(x.dot(start) >= 0.);
+            assert!(x.magnitude() <= offset.magnitude() + 1e-12);
+            offset = x;
+            velocity = v;
+        }
+        assert!(offset.magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn spring_steps_add_up() {
+        // The exact solution doesn't depend on the step's length
+        let (offset, velocity) = (vec3(2., 0., 0.), vec3(0., 3., 0.));
+        let (x, v) = spring(offset, velocity, 150., 0.1);
+        let (y, w) = spring(offset, velocity, 150., 0.05);
+        let (y, w) = spring(y, w, 150., 0.05);
+        assert!((x - y).magnitude() < 1e-12);
+        assert!((v - w).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn shaking_calms_down() {
+        assert_eq!(shake(0., 1.3), vec3(0., 0., 0.));
+        assert!(calm(1., 0.5) < 1.);
+        assert!(calm(1., 0.5) > calm(1., 1.));
+    }
+}
@@ -0,0 +1,165 @@
+// Hidden-line removal: lines are split where a box between them and
+// the camera hides them.
+use super::camera::Camera;
+use crate::color::Color;
+use crate::display::{DisplayList, Primitive};
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+// How to draw lines hidden behind boxes
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HiddenLines {
+    Show, // Draw every line, boxes look like wire cages
+    Hide,
+    Dash,
+}
+
+// An axis-aligned box that hides what is behind it
+#[derive(Clone, Copy, Debug)]
+pub struct Occluder {
+    pub min: Vector3<f64>,
+    pub max: Vector3<f64>,
+}
+
+// Lines are checked at this many points, then the boundaries between
+// visible and hidden parts are refined.
+const SAMPLES: usize = 24;
+const REFINE: usize = 8;
+// Tolerance for points lying on a box's surface
+const EPSILON: f64 = 1e-6;
+// Length of dashes and gaps of hidden lines, in pixels
+const DASH: f64 = 6.;
+const GAP: f64 = 4.;
+
+impl Occluder {
+    // Return true if the box is crossed on the way from `eye` to `p`
+    fn hides(&self, eye: Vector3<f64>, p: Vector3<f64>) -> bool {
+        let d = p - eye;
+        let (mut enter, mut exit) = (0f64, 1f64);
+        for i in 0..3 {
+            if d[i].abs() < EPSILON {
+                if eye[i] <= self.min[i] || eye[i] >= self.max[i] {
+                    return false;
+                }
+            } else {
+                let a = (self.min[i] - eye[i]) / d[i];
+                let b = (self.max[i] - eye[i]) / d[i];
+                enter = enter.max(a.min(b));
+                exit = exit.min(a.max(b));
+            }
+        }
+        // Grazing a box or reaching its surface doesn't hide `p`
+        let scale = EPSILON * d.magnitude().max(1.);
+        enter + scale < exit && enter < 1. - scale
+    }
+}
+
+// Return true if no box hides `p` from the camera
+fn visible(p: Vector3<f64>, camera: &Camera, boxes: &[Occluder]) -> bool {
+    let eye = camera.ray_origin(p);
+    !boxes.iter().any(|x| x.hides(eye, p))
+}
+
+// Split a line into parts that are visible or hidden from the camera
+pub fn split(
+    [a, b]: [Vector3<f64>; 2],
+    camera: &Camera,
+    boxes: &[Occluder],
+) -> Vec<([Vector3<f64>; 2], bool)> {
+    let at = |t: f64| a + t * (b - a);
+    let mut parts = Vec::new();
+    let mut start = 0.;
+    let mut state = visible(a, camera, boxes);
+    for i in 1..=SAMPLES {
+        let t = i as f64 / SAMPLES as f64;
+        if visible(at(t), camera, boxes) != state {
+            // Find where the visibility changes
+            let (mut lo, mut hi) = ((i - 1) as f64 / SAMPLES as f64, t);
+            for _ in 0..REFINE {
+                let mid = (lo + hi) / 2.;
+                if visible(at(mid), camera, boxes) == state {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            parts.push(([at(start), at(hi)], state));
+            start = hi;
+            state = !state;
+        }
+    }
+    parts.push(([at(start), b], state));
+    parts
+}
+
+// Render lines of `width` with the parts hidden behind `boxes` drawn
+// as `mode`
+pub fn render(
+    lines: &[([Vector3<f64>; 2], Color)],
+    camera: &Camera,
+    boxes: &[Occluder],
+    mode: HiddenLines,
+    width: f64,
+) -> DisplayList {
+    let mut ret = Vec::new();
+    for &(line, color) in lines {
+        let parts = match mode {
+            HiddenLines::Show => vec![(line, true)],
+            _ => split(line, camera, boxes),
+        };
+        for ([a, b], visible) in parts {
+            let depth = camera.depth((a + b) / 2.);
+            let line = camera
+                .render_line(&a, &b)
+                .map(|x| Primitive::line(x, width, color).at(depth));
+            match line {
+                Some(x) if visible => ret.push(x),
+                Some(x) if mode == HiddenLines::Dash => ret.push(x.dashed(DASH, GAP)),
+                _ => (),
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Fov, Projection};
+    use cgmath::vec3;
+
+    // A camera at the origin looking down the z axis, with a box in
+    // front of it
+    fn scene() -> (Camera, Vec<Occluder>) {
+        let projection = Projection::Perspective(Fov::Horizontal(90.));
+        let camera = Camera::new(crate::Pixel::new(200, 200), vec3(0., 0., 0.), projection);
+        let boxes = vec![Occluder {
+            min: vec3(-1., -1., 5.),
+            max: vec3(1., 1., 6.),
+        }];
+        (camera, boxes)
+    }
+
+    #[test]
+    fn line_behind_a_box_is_split() {
+        let (camera, boxes) = scene();
+        let parts = split([vec3(-5., 0., 10.), vec3(5., 0., 10.)], &camera, &boxes);
+        let states: Vec<_> = parts.iter().map(|x| x.1).collect();
+        assert_eq!(states, [true, false, true]);
+        // The box's front face hides x from -2 to 2 at twice its depth
+        let [a, b] = parts[1].0;
+        assert!((a.x + 2.).abs() < 0.01 && (b.x - 2.).abs() < 0.01);
+        assert_eq!(parts[0].0[0], vec3(-5., 0., 10.));
+        assert_eq!(parts[2].0[1], vec3(5., 0., 10.));
+    }
+
+    #[test]
+    fn line_in_front_of_a_box_is_visible() {
+        let (camera, boxes) = scene();
+        let line = [vec3(-5., 0., 3.), vec3(5., 0., 3.)];
+        let parts = split(line, &camera, &boxes);
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].1);
+    }
+}
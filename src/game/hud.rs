@@ -0,0 +1,66 @@
+// Labeled readings of the game, drawn as a panel in a corner of the
+// window over the game.
+use super::minimap::Corner;
+use crate::color::*;
+use crate::display::{DisplayList, Primitive};
+use cgmath::vec2;
+
+// Sizes in the text's height: space around the panel, height of
+// a row, and where values start in a row
+const MARGIN: f64 = 0.5;
+const ROW: f64 = 1.5;
+const VALUE: f64 = 5.;
+const WIDTH: f64 = 10.;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hud {
+    pub corner: Corner,
+    pub text_size: f64, // Height of text, as a part of the window's height
+    pub opacity: f64,   // From 0 to 1
+}
+
+// A labeled value, with a gauge behind it filled from 0 to 1
+pub struct Reading {
+    pub label: &'static str,
+    pub value: String,
+    pub gauge: Option<(f64, Color)>,
+}
+
+impl Hud {
+    // Draw `readings` as rows of a panel in a window of `size`
+    pub fn render(&self, readings: &[Reading], size: crate::Pixel) -> DisplayList {
+        let text = self.text_size * f64::from(size.h);
+        let (w, h) = (WIDTH * text, (readings.len() as f64 * ROW + MARGIN) * text);
+        let margin = MARGIN * text;
+        let (x, y) = self.corner.place(w, h, margin, size);
+        let fade = |color: Color| color.fade(self.opacity as f32);
+
+        let mut ret = vec![Primitive::rectangle([x, y, w, h], fade(BLACK))];
+        for (i, reading) in readings.iter().enumerate() {
+            let top = y + margin + i as f64 * ROW * text;
+            if let Some((full, color)) = reading.gauge {
+                let full = full.clamp(0., 1.);
+                ret.push(Primitive::rectangle(
+                    [x, top, w * full, ROW * text],
+                    fade(color.alpha(0.4)),
+                ));
+            }
+            // Text is placed by its baseline
+            let baseline = top + text;
+            ret.push(Primitive::text(
+                vec2(x + margin, baseline),
+                text,
+                reading.label,
+                fade(GRAY),
+            ));
+            ret.push(Primitive::text(
+                vec2(x + VALUE * text, baseline),
+                text,
+                &reading.value,
+                fade(WHITE),
+            ));
+        }
+        ret
+    }
+}
@@ -0,0 +1,47 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::GameConfig;
+
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Read a JSON file
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> io::Result<T> {
+    let mut s = String::new();
+    File::open(path)?.read_to_string(&mut s)?;
+    Ok(serde_json::from_str(&s)?)
+}
+
+// Write `x` to a compact JSON file
+pub fn save<T: Serialize, P: AsRef<Path>>(x: &T, path: P) -> io::Result<()> {
+    File::create(path)?.write_all(serde_json::to_string(x)?.as_bytes())
+}
+
+// Return an error if a file's version is not the expected one
+pub fn check_version(kind: &str, version: u32, expected: u32) -> io::Result<()> {
+    if version == expected {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} version {} is not supported, expected {}",
+                kind, version, expected
+            ),
+        ))
+    }
+}
+
+// Return an error if a config read from a file can't be run
+pub fn check_config(kind: &str, config: &GameConfig) -> io::Result<()> {
+    config.validate(None).map_err(|errors| {
+        let errors: Vec<_> = errors.iter().map(|x| x.to_string()).collect();
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has an invalid config: {}", kind, errors.join(", ")),
+        )
+    })
+}
@@ -0,0 +1,189 @@
+use super::config::{field_line, set_value, ConfigError, GameConfig};
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// A config built from layers, each layer overrides the fields it
+// sets: built-in defaults, then config files, then the command line.
+pub struct LayeredConfig {
+    value: Value,                    // Merged config
+    layers: Vec<Layer>,              // Layers added so far
+    origins: HashMap<String, usize>, // Last layer that set each field
+}
+
+struct Layer {
+    name: String,           // Shown in error messages, e.g. a file's path
+    source: Option<String>, // JSON source, to find fields' lines
+}
+
+impl LayeredConfig {
+    // Start with the default config
+    pub fn new() -> LayeredConfig {
+        LayeredConfig {
+            value: serde_json::to_value(GameConfig::default()).unwrap_or(Value::Null),
+            layers: Vec::new(),
+            origins: HashMap::new(),
+        }
+    }
+
+    // Add a layer from a JSON source, which may set only some fields,
+    // even of nested objects
+    pub fn add_json(&mut self, name: &str, source: &str) -> Result<(), Vec<ConfigError>> {
+        let map = match serde_json::from_str(source).map_err(|e| vec![e.into()])? {
+            Value::Object(map) => map,
+            _ => {
+                return Err(vec![ConfigError::Parse {
+                    line: 1,
+                    column: 1,
+                    message: "expected an object of config fields".to_owned(),
+                }])
+            }
+        };
+        // Read each field merged over the layers below, so unknown
+        // fields and wrong types are reported with their lines.
+        let mut errors = Vec::new();
+        for (k, v) in &map {
+            let mut value = self.value.clone();
+            merge(&mut value[k], v.clone());
+            if let Err(e) = serde_json::from_value::<GameConfig>(value) {
+                errors.push(field_error(source, k, &e));
+            }
+        }
+        if !errors.is_empty() {
+            errors.sort_by_key(|x| match x {
+                ConfigError::Parse { line, .. } => *line,
+                ConfigError::Invalid { line, .. } => line.unwrap_or(0),
+            });
+            return Err(errors);
+        }
+        for (k, v) in map {
+            merge(&mut self.value[&k], v);
+            self.origins.insert(k, self.layers.len());
+        }
+        self.layers.push(Layer {
+            name: name.to_owned(),
+            source: Some(source.to_owned()),
+        });
+        Ok(())
+    }
+
+    // Set a single field in the layer named `name`, see `set_value`
+    pub fn set(&mut self, name: &str, field: &str, value: &str) -> Result<(), String> {
+        set_value(&mut self.value, field, value)?;
+        // Check the type of the new value now, while we know which
+        // field it is for.
+        serde_json::from_value::<GameConfig>(self.value.clone())
+            .map_err(|e| format!("Invalid value `{}` for `{}`: {}", value, field, e))?;
+        if self.layers.last().map(|x| x.name != name).unwrap_or(true) {
+            self.layers.push(Layer {
+                name: name.to_owned(),
+                source: None,
+            });
+        }
+        let top = field.split('.').next().unwrap_or(field);
+        self.origins.insert(top.to_owned(), self.layers.len() - 1);
+        Ok(())
+    }
+
+    // Return the merged config, or the errors found in it with the
+    // name of the layer that set each wrong field.
+    pub fn build(&self) -> Result<GameConfig, Vec<(String, ConfigError)>> {
+        let config: GameConfig = serde_json::from_value(self.value.clone())
+            .map_err(|e| vec![("merged config".to_owned(), e.into())])?;
+        let errors: Vec<_> = config
+            .check()
+            .into_iter()
+            .map(|(field, message)| {
+                let layer = self.origins.get(field).map(|&i| &self.layers[i]);
+                let name = layer.map_or("default config", |x| &x.name).to_owned();
+                let line = layer
+                    .and_then(|x| x.source.as_ref())
+                    .and_then(|x| field_line(x, field));
+                let error = ConfigError::Invalid {
+                    field,
+                    line,
+                    message,
+                };
+                (name, error)
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for LayeredConfig {
+    fn default() -> LayeredConfig {
+        LayeredConfig::new()
+    }
+}
+
+// Error in the value of `field` set in a JSON source, placed at the
+// field's name
+fn field_error(source: &str, field: &str, e: &serde_json::Error) -> ConfigError {
+    let line = field_line(source, field).unwrap_or(1);
+    let key = format!("\"{}\"", field);
+    let column = source
+        .lines()
+        .nth(line - 1)
+        .and_then(|x| x.find(&key))
+        .map_or(1, |x| x + 1);
+    ConfigError::Parse {
+        line,
+        column,
+        message: format!("`{}`: {}", field, e),
+    }
+}
+
+// Merge `layer` into `base`, objects are merged field by field,
+// any other value replaces the old one.
+fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (k, v) in layer {
+                merge(base.entry(k).or_insert(Value::Null), v);
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+// Return the path of the user's config file, which overrides the
+// shipped one: `$XDG_CONFIG_HOME/boxcrash/config.json`,
+// `%APPDATA%\boxcrash\config.json` or `~/.config/boxcrash/config.json`.
+pub fn user_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .or_else(|| env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|x| PathBuf::from(x).join(".config")))?;
+    Some(dir.join("boxcrash").join("config.json"))
+}
+
+// Set a field in the config file at `path`, the file's other fields
+// are kept. The file and its directory are created if needed.
+pub fn save_field(path: &Path, field: &str, value: Value) -> io::Result<()> {
+    let mut config = match std::fs::read_to_string(path) {
+        Ok(source) => serde_json::from_str(&source)?,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Value::Object(Default::default()),
+        Err(e) => return Err(e),
+    };
+    match config {
+        Value::Object(ref mut map) => merge(map.entry(field).or_insert(Value::Null), value),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "config is not a JSON object",
+            ))
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&config)? + "\n")
+}
@@ -0,0 +1,108 @@
+// A map of the tunel seen from above, drawn in a corner of the window
+// over the game.
+use super::car::BoxCar;
+use super::world::World;
+use crate::color::*;
+use crate::display::{DisplayList, Primitive};
+use cgmath::{vec2, vec3, Vector2, Vector3};
+
+// Distance from the map to the window's edges, in pixels
+const MARGIN: f64 = 10.;
+const LINE_WIDTH: f64 = 1.;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    // Top left of a `w` by `h` box in this corner of a window of
+    // `size`, `margin` away from its edges
+    pub fn place(self, w: f64, h: f64, margin: f64, size: crate::Pixel) -> (f64, f64) {
+        let x = match self {
+            Corner::TopLeft | Corner::BottomLeft => margin,
+            Corner::TopRight | Corner::BottomRight => f64::from(size.w) - margin - w,
+        };
+        let y = match self {
+            Corner::TopLeft | Corner::TopRight => margin,
+            Corner::BottomLeft | Corner::BottomRight => f64::from(size.h) - margin - h,
+        };
+        (x, y)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Minimap {
+    pub corner: Corner,
+    pub scale: f64,   // Pixels per world's unit
+    pub opacity: f64, // From 0 to 1
+}
+
+impl Minimap {
+    // Draw the tunel's road with the bots, the player and the bullets
+    // on it, in a window of `size`. The far end of the tunel is up.
+    pub fn render(&self, world: &World, size: crate::Pixel) -> DisplayList {
+        let tunel = world.tunel.size;
+        let (w, h) = (tunel.x * self.scale, tunel.z * self.scale);
+        let (x, y) = self.corner.place(w, h, MARGIN, size);
+        let fade = |color: Color| color.fade(self.opacity as f32);
+        // Things are cut to the road
+        let point = |p: Vector3<f64>| {
+            vec2(
+                x + p.x.max(0.).min(tunel.x) * self.scale,
+                y + h - p.z.max(0.).min(tunel.z) * self.scale,
+            )
+        };
+        let rectangle = |a: Vector2<f64>, b: Vector2<f64>, color: Color| {
+            let (min, max) = (
+                vec2(a.x.min(b.x), a.y.min(b.y)),
+                vec2(a.x.max(b.x), a.y.max(b.y)),
+            );
+            Primitive::rectangle([min.x, min.y, max.x - min.x, max.y - min.y], fade(color))
+        };
+        // A car's position is the middle of its back
+        let car = |car: &BoxCar| {
+            let half = vec3(car.size.x / 2., 0., 0.);
+            let length = vec3(0., 0., car.size.z);
+            rectangle(
+                point(car.position - half),
+                point(car.position + half + length),
+                car.color,
+            )
+        };
+
+        let mut ret = vec![rectangle(vec2(x, y), vec2(x + w, y + h), BLACK)];
+        let corners = [
+            vec2(x, y),
+            vec2(x + w, y),
+            vec2(x + w, y + h),
+            vec2(x, y + h),
+        ];
+        for (a, b) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+            ret.push(Primitive::line(
+                [*a, *b],
+                LINE_WIDTH,
+                fade(world.tunel.color),
+            ));
+        }
+        for bot in &world.bots {
+            ret.push(car(&bot.car));
+        }
+        if !world.wrecked {
+            ret.push(car(&world.player));
+        }
+        for x in &world.bullets {
+            ret.push(Primitive::line(
+                [point(x[0]), point(x[0] + x[1])],
+                LINE_WIDTH,
+                fade(world.player.color),
+            ));
+        }
+        ret
+    }
+}
@@ -1,8 +1,40 @@
 mod bot;
 mod camera;
 mod car;
-mod game;
+mod config;
+mod debris;
+mod fog;
+mod follow;
+mod hidden;
+mod hud;
+mod json;
+mod layered;
+mod minimap;
+mod replay;
+mod simulation;
+mod snapshot;
+mod solid;
+mod streaks;
+mod timestep;
 mod tunel;
 mod world;
 
-pub use self::game::{Game, GameConfig};
+pub use self::bot::{Action, Bot, BoxRules};
+pub use self::camera::{Camera, CameraMode, CameraViews, Fov, Projection, View};
+pub use self::car::{BoxCar, Car};
+pub use self::config::{ConfigError, GameConfig};
+pub use self::debris::{Debris, Piece};
+pub use self::fog::Fog;
+pub use self::follow::Shake;
+pub use self::hidden::{HiddenLines, Occluder};
+pub use self::hud::{Hud, Reading};
+pub use self::layered::{save_field, user_config_path, LayeredConfig};
+pub use self::minimap::{Corner, Minimap};
+pub use self::replay::{Playback, Replay, REPLAY_VERSION};
+pub use self::simulation::{Command, Control, Frame, Simulation, State, Turn};
+pub use self::snapshot::{Snapshot, SNAPSHOT_VERSION};
+pub use self::solid::Face;
+pub use self::streaks::SpeedLines;
+pub use self::timestep::FixedStep;
+pub use self::tunel::Tunel;
+pub use self::world::{Layer, RenderedLayer, World};
@@ -0,0 +1,88 @@
+use super::json;
+use super::simulation::{Command, Simulation};
+use super::GameConfig;
+
+use std::io;
+use std::path::Path;
+
+// Version of replay files, increase it when the format or the game's
+// logic changes in a way that old replays can't be played.
+pub const REPLAY_VERSION: u32 = 1;
+
+// A recorded run: the config (with its seed), then every command,
+// reloaded configs included, with the update it was applied before.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub config: GameConfig,
+    pub ticks: u64, // Number of updates in the run
+    pub commands: Vec<(u64, Command)>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+        let replay: Replay = json::load(path)?;
+        json::check_version("Replay", replay.version, REPLAY_VERSION)?;
+        json::check_config("Replay", &replay.config)?;
+        for (_, x) in &replay.commands {
+            if let Command::Reconfigure(config) = x {
+                json::check_config("Replay", config)?;
+            }
+        }
+        Ok(replay)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        json::save(self, path)
+    }
+
+    // Play the whole replay without a window, return the game at
+    // its last update
+    pub fn play(self) -> Simulation {
+        let mut sim = Simulation::new(self.config.clone());
+        let mut playback = Playback::new(self);
+        loop {
+            let tick = sim.ticks();
+            playback.apply(&mut sim, tick);
+            if playback.finished(tick) || sim.ended() {
+                return sim;
+            }
+            sim.step();
+        }
+    }
+}
+
+// Feed a simulation with the commands of a replay
+pub struct Playback {
+    replay: Replay,
+    next: usize, // Index of the next command
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback { replay, next: 0 }
+    }
+
+    // Return the commands to apply before update number `tick`
+    pub fn commands(&mut self, tick: u64) -> &[(u64, Command)] {
+        let start = self.next;
+        let commands = &self.replay.commands;
+        while self.next < commands.len() && commands[self.next].0 <= tick {
+            self.next += 1;
+        }
+        &commands[start..self.next]
+    }
+
+    // Apply the commands of update number `tick` to `sim`, in the
+    // order they were recorded
+    pub fn apply(&mut self, sim: &mut Simulation, tick: u64) {
+        for (_, x) in self.commands(tick) {
+            sim.apply(x.clone());
+        }
+    }
+
+    // The replay has no more commands and reached its last update
+    pub fn finished(&self, tick: u64) -> bool {
+        self.next >= self.replay.commands.len() && tick >= self.replay.ticks
+    }
+}
@@ -0,0 +1,569 @@
+use super::bot::BoxRules;
+use super::camera::{Camera, CameraMode, View};
+use super::car::*;
+use super::follow;
+use super::hud::Reading;
+use super::replay::{Replay, REPLAY_VERSION};
+use super::snapshot::{Snapshot, SNAPSHOT_VERSION};
+use super::world::{RenderedLayer, World};
+use super::GameConfig;
+use crate::color::*;
+use crate::GameRng;
+
+use cgmath::prelude::*;
+use cgmath::{vec3, Vector3};
+use rand::SeedableRng;
+
+// Controls of the player, independent of any input device
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Control {
+    Left,
+    Right,
+    Sprint,
+    Jump,
+    Stare, // Stare mode, rotate camera with mouse
+    Shoot,
+    NextCamera, // Switch to the next camera mode
+}
+
+// An input command for the simulation
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Command {
+    Press(Control),
+    Release(Control),
+    MouseMove(f64, f64),
+    Reconfigure(Box<GameConfig>), // A reloaded config, see `Simulation::reconfigure`
+}
+
+// The world and camera between the last two updates, as they are
+// drawn
+pub struct Frame {
+    pub world: World,
+    pub camera: Camera,
+}
+
+// `Simulation` contains the game's logic, it does not need
+// a window to run.
+pub struct Simulation {
+    pub config: GameConfig,
+    pub world: World,        // All objects in the game
+    pub bot_rules: BoxRules, // Rules to create a new bot
+    pub camera: Camera,      // Camera for rendering and aiming
+    state: State,            // Current state of game
+    seed: u64,               // Seed of `rng`
+    rng: GameRng,            // The only source of randomness
+    ticks: u64,              // Number of updates done
+    // The config at the start of the run, then every applied command,
+    // reloaded configs included, in order with the update it was
+    // applied before, for replays.
+    initial_config: GameConfig,
+    commands: Vec<(u64, Command)>,
+    // The world and camera's movement before the last update,
+    // used to interpolate between updates while rendering.
+    previous: World,
+    camera_move: Vector3<f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct State {
+    pub turn: Turn,        // Presents movement of player
+    pub sprint: bool,      // Player is speeding-up or not
+    pub spawn: f64,        // Count down time to spawn a new bot
+    pub ended: bool,       // Game is over or not
+    pub game_speed: f64,   // Game speed in addition to player's speed
+    pub jump_timeout: f64, // Count down to allow the next jump
+    pub rotate_cam: bool,  // Allow rotation of camera or not
+    pub bullets: i64,      // The number of bullets left
+    pub recharge: f64,     // Bullets recharge time
+    pub camera_mode: CameraMode,
+    pub camera_from: View, // View the camera eases from to its mode's
+    pub camera_ease: f64,  // Progress of the easing, from 0 to 1
+    pub camera_velocity: Vector3<f64>, // Velocity of the camera's spring
+    pub shake: f64,        // Strength of the camera's shaking
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Turn {
+    Left,
+    Right,
+    None,
+}
+
+impl Simulation {
+    pub fn new(config: GameConfig) -> Simulation {
+        let bot_rules = Simulation::bot_rules(&config);
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let world = World::new(&config);
+        let camera =
+            Simulation::new_camera(&config, &world.player, &config.view(CameraMode::Chase));
+        let state = State {
+            turn: Turn::None,
+            sprint: false,
+            spawn: 0.,
+            ended: false,
+            game_speed: 0.,
+            jump_timeout: 0.,
+            rotate_cam: false,
+            bullets: config.bullet_stock,
+            recharge: 0.,
+            camera_mode: CameraMode::Chase,
+            camera_from: config.view(CameraMode::Chase),
+            camera_ease: 1.,
+            camera_velocity: vec3(0., 0., 0.),
+            shake: 0.,
+        };
+
+        Simulation {
+            initial_config: config.clone(),
+            config,
+            previous: world.clone(),
+            camera_move: vec3(0., 0., 0.),
+            world,
+            bot_rules,
+            camera,
+            state,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            ticks: 0,
+            commands: Vec::new(),
+        }
+    }
+
+    // Continue a game from a snapshot
+    pub fn from_snapshot(snapshot: Snapshot) -> Simulation {
+        Simulation {
+            config: snapshot.config,
+            previous: snapshot.world.clone(),
+            camera_move: vec3(0., 0., 0.),
+            world: snapshot.world,
+            bot_rules: snapshot.bot_rules,
+            camera: snapshot.camera,
+            state: snapshot.state,
+            seed: snapshot.seed,
+            rng: snapshot.rng,
+            ticks: snapshot.ticks,
+            initial_config: snapshot.initial_config,
+            commands: snapshot.commands,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            config: self.config.clone(),
+            world: self.world.clone(),
+            bot_rules: self.bot_rules.clone(),
+            camera: self.camera.clone(),
+            state: self.state.clone(),
+            seed: self.seed,
+            rng: self.rng.clone(),
+            ticks: self.ticks,
+            initial_config: self.initial_config.clone(),
+            commands: self.commands.clone(),
+        }
+    }
+
+    fn bot_rules(config: &GameConfig) -> BoxRules {
+        BoxRules {
+            size: config.bot_size,
+            position: [
+                (0., config.tunel_size[0]),
+                (0., 0.),
+                (config.tunel_size[2], config.tunel_size[2]),
+            ],
+            speed: config.bot_speed,
+            turn_speed: config.bot_turn_speed,
+            color: vec![RED, ORANGE, VIOLET, GREEN, PALE],
+            jump_turn_decrease: config.jump_turn_decrease,
+        }
+    }
+
+    // Apply a new config from the next update on. Fields that can't
+    // change during a game keep their old values, the ones that were
+    // changed are returned.
+    pub fn reconfigure(&mut self, mut config: GameConfig) -> Vec<&'static str> {
+        let kept = config.keep_restart_fields(&self.config);
+        self.apply(Command::Reconfigure(Box::new(config)));
+        kept
+    }
+
+    // Apply a config whose restart fields are kept already
+    fn set_config(&mut self, mut config: GameConfig) {
+        // The window's size is followed with `resize`
+        config.screen_size = self.config.screen_size;
+        self.bot_rules = Simulation::bot_rules(&config);
+        self.world.tunel.reconfigure(&config);
+        let player = &mut self.world.player;
+        player.turn_speed = config.player_turn_speed;
+        player.jump_v = config.player_jump_v;
+        player.jump_a = config.player_jump_a;
+        player.jump_turn_decrease = config.jump_turn_decrease;
+        self.state.bullets = i64::min(self.state.bullets, config.bullet_stock);
+        self.camera.set_projection(config.projection());
+        self.config = config;
+    }
+
+    // Render to a window of a new size, this doesn't change the game
+    pub fn resize(&mut self, size: crate::Pixel) {
+        self.config.screen_size = size;
+        self.camera.resize(size);
+    }
+
+    fn new_camera<T: Car>(config: &GameConfig, player: &T, view: &View) -> Camera {
+        let mut camera = Camera::new(config.screen_size, player.pos(), config.projection());
+        camera.follow(player.pos(), view);
+        camera
+    }
+
+    // The camera's view, easing from `camera_from` to the mode's view
+    fn view(&self) -> View {
+        let t = self.state.camera_ease;
+        let to = self.config.view(self.state.camera_mode);
+        self.state.camera_from.lerp(&to, t * t * (3. - 2. * t))
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    // Return the run so far as a replay
+    pub fn replay(&self) -> Replay {
+        let mut config = self.initial_config.clone();
+        config.seed = Some(self.seed);
+        Replay {
+            version: REPLAY_VERSION,
+            config,
+            ticks: self.ticks,
+            commands: self.commands.clone(),
+        }
+    }
+
+    pub fn ended(&self) -> bool {
+        self.state.ended
+    }
+
+    // The game has ended and the debris of the crash have faded out
+    pub fn finished(&self) -> bool {
+        self.state.ended && self.world.debris.is_empty()
+    }
+
+    // Duration of one update
+    pub fn tick(&self) -> f64 {
+        1. / self.config.ups as f64
+    }
+
+    // Render the game between the last two updates, `alpha` is the
+    // progress from the previous update to the last one.
+    pub fn render(&self, alpha: f64) -> crate::display::DisplayList {
+        let frame = self.frame(alpha);
+        frame.world.render(&frame.camera, &self.config)
+    }
+
+    // Render a frame like `render`, each layer separately
+    pub fn render_layers(&self, frame: &Frame) -> Vec<RenderedLayer> {
+        frame.world.render_layers(&frame.camera, &self.config)
+    }
+
+    // Render the minimap of a frame, nothing if it is turned off
+    pub fn render_minimap(&self, frame: &Frame) -> crate::display::DisplayList {
+        match &self.config.minimap {
+            Some(x) => x.render(&frame.world, self.config.screen_size),
+            None => Vec::new(),
+        }
+    }
+
+    // Render the readings of a frame, nothing if the HUD is turned off
+    pub fn render_hud(&self, frame: &Frame) -> crate::display::DisplayList {
+        let hud = match &self.config.hud {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+        let world = &frame.world;
+        let (state, config) = (&self.state, &self.config);
+        // Time left of a count down
+        let seconds = |x: f64| {
+            if x > 0. {
+                format!("{:.1} s", x)
+            } else {
+                "-".to_owned()
+            }
+        };
+        let readings = [
+            Reading {
+                label: "Speed",
+                value: format!("{:.0}", world.speed),
+                gauge: None,
+            },
+            Reading {
+                label: "Distance",
+                value: format!("{:.0}", world.distance),
+                gauge: None,
+            },
+            Reading {
+                label: "Bullets",
+                value: format!("{}/{}", state.bullets.max(0), config.bullet_stock),
+                gauge: Some((state.bullets as f64 / config.bullet_stock as f64, GREEN)),
+            },
+            Reading {
+                label: "Recharge",
+                value: seconds(if state.bullets > 0 {
+                    0.
+                } else {
+                    state.recharge
+                }),
+                gauge: Some((state.recharge / config.recharge_time, RED)),
+            },
+            Reading {
+                label: "Jump",
+                value: seconds(state.jump_timeout),
+                gauge: Some((state.jump_timeout / config.jump_timeout, BLUE)),
+            },
+            Reading {
+                label: "Score",
+                value: world.score.to_string(),
+                gauge: None,
+            },
+        ];
+        hud.render(&readings, config.screen_size)
+    }
+
+    // The world and camera between the last two updates, `alpha` is
+    // the progress from the previous update to the last one. Build it
+    // once to render all parts of a frame.
+    pub fn frame(&self, alpha: f64) -> Frame {
+        let world = self.world.interpolate(&self.previous, alpha, self.tick());
+        let mut camera = self.camera.clone();
+        camera.eye -= (1. - alpha) * self.camera_move;
+        let time = (self.ticks as f64 - 1. + alpha) * self.tick();
+        camera.eye += follow::shake(self.state.shake, time);
+        // Widen the view as the player speeds up
+        let (min, max) = self.config.player_speed;
+        if max > min && !world.wrecked {
+            let t = ((world.player.speed - min) / (max - min)).clamp(0., 1.);
+            camera.set_projection(self.config.projection().widen(t * self.config.sprint_fov));
+        }
+        Frame { world, camera }
+    }
+
+    // Apply a command, commands are ignored once the game has ended
+    pub fn apply(&mut self, command: Command) {
+        if self.state.ended {
+            return;
+        }
+        match &command {
+            Command::Press(x) => self.press(*x),
+            Command::Release(x) => self.release(*x),
+            Command::MouseMove(x, y) => self.mouse_move(*x, *y),
+            Command::Reconfigure(x) => self.set_config((**x).clone()),
+        }
+        self.commands.push((self.ticks, command));
+    }
+
+    fn mouse_move(&mut self, x: f64, y: f64) {
+        if self.state.rotate_cam {
+            self.camera.rotate(
+                x * self.config.mouse_speed,
+                y * self.config.mouse_speed,
+                self.world.player.position,
+            );
+        }
+    }
+    fn press(&mut self, control: Control) {
+        match control {
+            Control::Left => self.state.turn = Turn::Left,
+            Control::Right => self.state.turn = Turn::Right,
+            Control::Sprint => self.state.sprint = true,
+            Control::Jump => {
+                if self.state.jump_timeout <= 0. {
+                    self.state.jump_timeout = self.config.jump_timeout;
+                    self.world.player.jump();
+                }
+            }
+            Control::Stare if self.state.camera_mode.can_aim() => {
+                if self.config.zoom_in {
+                    self.camera.zoom_in();
+                }
+                self.state.rotate_cam = true;
+            }
+            Control::Stare => (),
+            Control::NextCamera => {
+                let next = self.state.camera_mode.next();
+                if self.state.rotate_cam && !next.can_aim() {
+                    self.release(Control::Stare);
+                }
+                self.state.camera_from = self.view();
+                self.state.camera_mode = next;
+                self.state.camera_ease = 0.;
+            }
+            Control::Shoot => {
+                if self.state.rotate_cam && self.state.bullets > 0 {
+                    let mut pos = self.world.player.position;
+                    pos.y += self.world.player.size.y;
+                    let mut d = vec3(
+                        0.,
+                        0.,
+                        self.config.trueshot_distance + self.config.camera_distance,
+                    );
+                    d = self.camera.c * d.magnitude2() / d.dot(self.camera.c);
+                    d = self.camera.eye + d - pos;
+                    d = d * self.config.bullet_speed / d.magnitude();
+                    self.world.add_bullet(pos, d, self.config.bullet_len);
+                    self.state.bullets -= 1;
+                    if self.state.bullets <= 0 {
+                        self.state.recharge = self.config.recharge_time;
+                    }
+                }
+            }
+        }
+    }
+    fn release(&mut self, control: Control) {
+        match control {
+            Control::Left => {
+                if let Turn::Left = self.state.turn {
+                    self.state.turn = Turn::None;
+                }
+            }
+            Control::Right => {
+                if let Turn::Right = self.state.turn {
+                    self.state.turn = Turn::None;
+                }
+            }
+            Control::Sprint => self.state.sprint = false,
+            Control::Stare if self.state.rotate_cam => {
+                self.state.rotate_cam = false;
+                // Ease back from where the camera was turned to
+                let view = self.view();
+                let reach = (Vector3::from(view.look) - Vector3::from(view.eye)).magnitude();
+                let eye = self.camera.eye - self.world.player.position;
+                let look = eye + self.camera.c.normalize() * reach;
+                self.state.camera_from = View {
+                    eye: eye.into(),
+                    look: look.into(),
+                };
+                self.state.camera_ease = 0.;
+                self.state.camera_velocity = vec3(0., 0., 0.);
+            }
+            Control::Stare | Control::Jump | Control::Shoot | Control::NextCamera => (),
+        }
+    }
+
+    // Advance the game by one update
+    pub fn step(&mut self) {
+        let dt = self.tick();
+        self.update(dt);
+    }
+
+    // `dt` stands for delta, duration since the last update
+    pub fn update(&mut self, dt: f64) {
+        self.ticks += 1;
+        self.previous = self.world.clone();
+        // Once the game has ended, only debris still move
+        self.state.shake = follow::calm(self.state.shake, dt);
+        if self.state.ended {
+            self.camera_move = vec3(0., 0., 0.);
+            self.world.update_debris(dt, 0.);
+            self.world.expire_debris(self.config.debris_time);
+            return;
+        }
+        let old = self.world.player.position;
+        let jumping = self.world.player.jumping;
+        if self.state.bullets <= 0 {
+            self.state.recharge -= dt;
+            if self.state.recharge < 0. {
+                self.state.bullets = self.config.bullet_stock;
+            }
+        }
+        self.state.jump_timeout -= dt;
+        self.state.camera_ease = if self.config.camera_ease > 0. {
+            f64::min(self.state.camera_ease + dt / self.config.camera_ease, 1.)
+        } else {
+            1.
+        };
+        if self.state.game_speed < self.config.game_max_speed {
+            self.state.game_speed += dt * self.config.game_sprint;
+        }
+        if self.state.sprint {
+            if self.world.player.speed < self.config.player_speed.1 {
+                self.world.player.speed += dt * self.config.sprint_factor;
+            }
+        } else if self.world.player.speed > self.config.player_speed.0 {
+            self.world.player.speed -= dt * self.config.sprint_factor;
+        }
+        self.state.spawn -= dt;
+        if self.state.spawn < 0. {
+            self.world.add_bot(&self.bot_rules, &mut self.rng);
+            self.state.spawn += crate::rnd(&mut self.rng, self.config.spawn_time);
+        }
+        match self.state.turn {
+            Turn::Left => self.world.player.turn_left(dt),
+            Turn::Right => self.world.player.turn_right(dt),
+            Turn::None => (),
+        }
+        // Update objects in the world
+        self.world.update(dt, self.state.game_speed);
+        // Validate things like object's boundary, bullets and boxes
+        // collisions.
+        self.world.validate();
+        self.world.expire_debris(self.config.debris_time);
+        let shake = self.config.camera_shake;
+        if jumping && !self.world.player.jumping {
+            self.shake(shake.landing);
+        }
+        if self.world.near_misses(&self.previous, follow::NEAR_MISS) > 0 {
+            self.shake(shake.near_miss);
+        }
+        // Update camera's location, it keeps the direction it was
+        // turned to in stare mode
+        let eye = self.camera.eye;
+        if self.state.rotate_cam {
+            self.camera.eye += self.world.player.position - old;
+        } else {
+            self.follow(dt);
+        }
+        self.camera_move = self.camera.eye - eye;
+        // Check for player's collision with bot
+        if self
+            .world
+            .bots
+            .iter()
+            .any(|x| self.world.player.crashed(&x.car))
+        {
+            self.world.wreck();
+            self.state.ended = true;
+            self.shake(shake.crash);
+        }
+    }
+
+    // Move the camera toward its view of the player, lagging behind
+    // on a spring
+    fn follow(&mut self, dt: f64) {
+        let view = self.view();
+        let position = self.world.player.position;
+        let mut eye = position + Vector3::from(view.eye);
+        if self.config.camera_spring > 0. {
+            let (offset, velocity) = follow::spring(
+                self.camera.eye - eye,
+                self.state.camera_velocity,
+                self.config.camera_spring,
+                dt,
+            );
+            eye += offset;
+            self.state.camera_velocity = velocity;
+        }
+        self.camera
+            .look_at(eye, position + Vector3::from(view.look));
+    }
+
+    // Shake the camera at least as strong as `strength`
+    fn shake(&mut self, strength: f64) {
+        self.state.shake = self.state.shake.max(strength);
+    }
+}
@@ -0,0 +1,52 @@
+use super::bot::BoxRules;
+use super::camera::Camera;
+use super::json;
+use super::simulation::{Command, State};
+use super::world::World;
+use super::GameConfig;
+use crate::GameRng;
+
+use std::io;
+use std::path::Path;
+
+// Version of snapshot files, increase it when any saved type changes
+// after a release
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+// A game in progress: every live object, pending bot actions,
+// timers, bullets and the random generator, so the game continues
+// exactly where it was saved.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub config: GameConfig,
+    pub world: World,
+    pub bot_rules: BoxRules,
+    pub camera: Camera,
+    pub state: State,
+    pub seed: u64,
+    pub rng: GameRng,
+    pub ticks: u64,
+    // Keep the run's replay valid
+    pub initial_config: GameConfig,
+    pub commands: Vec<(u64, Command)>,
+}
+
+impl Snapshot {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Snapshot> {
+        let snapshot: Snapshot = json::load(path)?;
+        json::check_version("Snapshot", snapshot.version, SNAPSHOT_VERSION)?;
+        json::check_config("Snapshot", &snapshot.config)?;
+        json::check_config("Snapshot", &snapshot.initial_config)?;
+        for (_, x) in &snapshot.commands {
+            if let Command::Reconfigure(config) = x {
+                json::check_config("Snapshot", config)?;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        json::save(self, path)
+    }
+}
@@ -0,0 +1,74 @@
+// Solid rendering: faces are filled with a color shaded by their
+// orientation to a fixed light.
+use super::camera::Camera;
+use super::fog::Fog;
+use crate::color::Color;
+use crate::display::{DisplayList, Primitive};
+use cgmath::prelude::*;
+use cgmath::{vec3, Vector3};
+
+// Light comes from above, a bit from the left and behind the camera
+const LIGHT: [f64; 3] = [-0.4, 1., -0.6];
+// Part of the light that reaches faces turned away from it
+const AMBIENT: f64 = 0.35;
+
+// A flat quad with the direction it faces
+#[derive(Clone, Copy, Debug)]
+pub struct Face {
+    pub points: [Vector3<f64>; 4],
+    pub normal: Vector3<f64>,
+}
+
+impl Face {
+    pub fn new(points: [Vector3<f64>; 4], normal: Vector3<f64>) -> Face {
+        Face { points, normal }
+    }
+
+    pub(super) fn centre(&self) -> Vector3<f64> {
+        self.points.iter().fold(vec3(0., 0., 0.), |a, &b| a + b) / 4.
+    }
+
+    // A face is seen from its front side only
+    fn facing(&self, camera: &Camera) -> bool {
+        let centre = self.centre();
+        self.normal.dot(camera.ray_origin(centre) - centre) > 0.
+    }
+}
+
+// Return `color` lit on a face facing `normal`, `brightness` scales
+// the result.
+pub fn shade(color: Color, normal: Vector3<f64>, brightness: f64) -> Color {
+    let light = Vector3::from(LIGHT).normalize();
+    let diffuse = f64::max(normal.normalize().dot(light), 0.);
+    color.scale((brightness * (AMBIENT + (1. - AMBIENT) * diffuse)) as f32)
+}
+
+// Render the faces seen by the camera with flat shading, faded into
+// `fog` by strips if it is set
+pub fn render(
+    faces: &[Face],
+    camera: &Camera,
+    color: Color,
+    brightness: f64,
+    fog: Option<&Fog>,
+) -> DisplayList {
+    faces
+        .iter()
+        .filter(|x| x.facing(camera))
+        .flat_map(|x| match fog {
+            Some(fog) => fog.cut(x, camera),
+            None => vec![*x],
+        })
+        .filter_map(|x| {
+            let color = shade(color, x.normal, brightness);
+            let depth = camera.depth(x.centre());
+            let color = match fog {
+                Some(fog) => fog.apply(color, depth),
+                None => color,
+            };
+            camera
+                .render_polygon(&x.points)
+                .map(|p| Primitive::polygon(p, color).at(depth))
+        })
+        .collect()
+}
@@ -0,0 +1,40 @@
+// Speed lines: streaks along the tunel's walls, more and longer ones
+// the faster the world moves toward the camera.
+use cgmath::{vec3, Vector3};
+
+// Most streaks drawn at once
+const MAX_STREAKS: usize = 256;
+// Streaks stay between these parts of the walls' height
+const LOW: f64 = 0.15;
+const HIGH: f64 = 0.85;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpeedLines {
+    pub start: f64,   // Speed from which streaks appear
+    pub density: f64, // Number of streaks per unit of speed above `start`
+    pub trail: f64,   // Length of streaks, in seconds of travel
+}
+
+impl SpeedLines {
+    // Streaks at `speed` in a tunel of `size`, after travelling
+    // `distance`
+    pub fn lines(&self, speed: f64, distance: f64, size: Vector3<f64>) -> Vec<[Vector3<f64>; 2]> {
+        if speed <= self.start {
+            return Vec::new();
+        }
+        let count = ((speed - self.start) * self.density) as usize;
+        let len = speed * self.trail;
+        (0..count.min(MAX_STREAKS))
+            .map(|i| {
+                // Spread the streaks evenly without a random generator,
+                // a streak keeps its place while others come and go
+                let x = if i % 2 == 0 { 0. } else { size.x };
+                let k = i as f64;
+                let y = size.y * (LOW + (HIGH - LOW) * (k * 0.618_034).fract());
+                let z = (size.z * (k * 0.754_878).fract() - distance).rem_euclid(size.z);
+                [vec3(x, y, z), vec3(x, y, z + len)]
+            })
+            .collect()
+    }
+}
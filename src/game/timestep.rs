@@ -0,0 +1,38 @@
+// Fixed timestep: real time is collected in an accumulator and
+// released in updates of the same length, so the game's physics
+// do not depend on frame rate.
+pub struct FixedStep {
+    pub tick: f64,    // Duration of one update
+    accumulator: f64, // Time not yet simulated
+    max_ticks: u32,   // Max number of updates to catch up in one frame
+}
+
+impl FixedStep {
+    pub fn new(ups: u64, max_ticks: u32) -> FixedStep {
+        FixedStep {
+            tick: 1. / ups as f64,
+            accumulator: 0.,
+            max_ticks,
+        }
+    }
+
+    // Add `dt` seconds of real time, return the number of updates
+    // to run. Time beyond `max_ticks` updates is dropped, so a long
+    // hitch slows the game down instead of freezing it.
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        self.accumulator += dt;
+        let ticks = (self.accumulator / self.tick).floor();
+        if ticks > f64::from(self.max_ticks) {
+            self.accumulator %= self.tick;
+            self.max_ticks
+        } else {
+            self.accumulator -= ticks * self.tick;
+            ticks as u32
+        }
+    }
+
+    // Progress from the last update to the next one, between 0 and 1
+    pub fn alpha(&self) -> f64 {
+        f64::min(self.accumulator / self.tick, 1.)
+    }
+}
@@ -1,7 +1,8 @@
-use super::camera::Camera;
+use super::solid::Face;
 use crate::color::*;
 use cgmath::{vec3, Vector2, Vector3};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tunel {
     // Size of tunel, x, y, z is the width, height, and deep of the
     // tunel.
@@ -28,7 +29,67 @@ impl Tunel {
         }
     }
 
-    pub fn render(&self, camera: &Camera) -> Vec<([Vector2<f64>; 2], Color)> {
+    // Apply a new config to the running tunel, its size can't change
+    pub fn reconfigure(&mut self, config: &crate::game::GameConfig) {
+        self.divider = config.divider_size.into();
+        self.divider_state = f64::min(self.divider_state, 2. * self.divider.y);
+        self.decor_distance = config.decor_distance;
+        self.decor_state = f64::min(self.decor_state, self.decor_distance);
+    }
+
+    // The tunel's road, walls and ceiling, facing inside
+    pub fn faces(&self) -> Vec<Face> {
+        let (x, y, z) = (self.size.x, self.size.y, self.size.z);
+        vec![
+            Face::new(
+                [
+                    vec3(0., 0., 0.),
+                    vec3(0., 0., z),
+                    vec3(x, 0., z),
+                    vec3(x, 0., 0.),
+                ],
+                vec3(0., 1., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(0., y, 0.),
+                    vec3(x, y, 0.),
+                    vec3(x, y, z),
+                    vec3(0., y, z),
+                ],
+                vec3(0., -1., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(0., 0., 0.),
+                    vec3(0., y, 0.),
+                    vec3(0., y, z),
+                    vec3(0., 0., z),
+                ],
+                vec3(1., 0., 0.),
+            ),
+            Face::new(
+                [
+                    vec3(x, 0., 0.),
+                    vec3(x, 0., z),
+                    vec3(x, y, z),
+                    vec3(x, y, 0.),
+                ],
+                vec3(-1., 0., 0.),
+            ),
+        ]
+    }
+
+    // Road's dividers as faces on the road
+    pub fn divider_faces(&self) -> Vec<Face> {
+        self.dividers()
+            .chunks(4)
+            .map(|x| Face::new([x[0][0], x[1][0], x[2][0], x[3][0]], vec3(0., 1., 0.)))
+            .collect()
+    }
+
+    // The tunel's four long edges
+    pub fn edges(&self) -> Vec<[Vector3<f64>; 2]> {
         vec![
             ((0., 0., 0.), (0., 0., self.size.z)),
             ((self.size.x, 0., 0.), (self.size.x, 0., self.size.z)),
@@ -39,10 +100,7 @@ impl Tunel {
             ),
         ]
         .into_iter()
-        .map(|(a, b)| camera.render_line(&a.into(), &b.into()))
-        .filter_map(|x| x.map(|x| (x, self.color)))
-        .chain(self.divider_render(camera))
-        .chain(self.decor_render(camera))
+        .map(|(a, b)| [a.into(), b.into()])
         .collect()
     }
 
@@ -57,7 +115,31 @@ impl Tunel {
         }
     }
 
-    fn divider_render(&self, camera: &Camera) -> crate::Rendered {
+    // Return the tunel between `prev` and `self`
+    pub fn interpolate(&self, prev: &Tunel, alpha: f64) -> Tunel {
+        // States count down and wrap around, so a state greater than
+        // the previous one has wrapped.
+        let lerp = |prev: f64, curr: f64, period: f64| {
+            let d = if curr > prev {
+                curr - prev - period
+            } else {
+                curr - prev
+            };
+            let x = prev + alpha * d;
+            if x < 0. {
+                x + period
+            } else {
+                x
+            }
+        };
+        Tunel {
+            divider_state: lerp(prev.divider_state, self.divider_state, 2. * self.divider.y),
+            decor_state: lerp(prev.decor_state, self.decor_state, self.decor_distance),
+            ..self.clone()
+        }
+    }
+
+    pub fn dividers(&self) -> Vec<[Vector3<f64>; 2]> {
         let mut points = [vec3(self.size.x / 2., 0., self.divider_state); 4];
         points[2].z -= self.divider.y;
         points[3].z -= self.divider.y;
@@ -72,9 +154,8 @@ impl Tunel {
                 let iter = p
                     .iter()
                     .zip(p.iter().cycle().skip(1))
-                    .map(|(x, y)| camera.render_line(x, y))
-                    .filter_map(|x| x.map(|x| (x, self.color)));
-                ret.append(&mut iter.collect());
+                    .map(|(x, y)| [*x, *y]);
+                ret.extend(iter);
             };
             while points[0].z <= self.size.z {
                 r(&points);
@@ -86,7 +167,7 @@ impl Tunel {
         }
         ret
     }
-    fn decor_render(&self, camera: &Camera) -> crate::Rendered {
+    pub fn decor(&self) -> Vec<[Vector3<f64>; 2]> {
         let mut data = [
             vec3(0., 0., self.decor_state),
             vec3(0., self.size.y, self.decor_state),
@@ -96,9 +177,7 @@ impl Tunel {
         let mut ret = Vec::new();
         while data[0].z <= self.size.z {
             for (x, y) in data.iter().zip(data.iter().skip(1)) {
-                if let Some(rendered) = camera.render_line(x, y) {
-                    ret.push((rendered, self.color));
-                }
+                ret.push([*x, *y]);
             }
             for x in &mut data {
                 x.z += self.decor_distance;
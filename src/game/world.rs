@@ -1,17 +1,69 @@
 use super::bot::{Bot, BoxRules};
 use super::camera::Camera;
 use super::car::*;
+use super::debris::Debris;
+use super::hidden::{self, HiddenLines};
+use super::solid::{self, Face};
 use super::tunel::Tunel;
 use super::GameConfig;
 use crate::color::*;
+use crate::display::DisplayList;
 use cgmath::prelude::*;
 use cgmath::{vec3, Vector3};
 
+// Brightness of the tunel's faces, so they stay behind the boxes
+const TUNEL_BRIGHTNESS: f64 = 0.3;
+// Width of rendered lines, in pixels
+const LINE_WIDTH: f64 = 2.;
+// How much lighter than the tunel speed lines are, from 0 to 1
+const STREAK_WHITENESS: f32 = 0.5;
+
+// Sources of rendered lines and faces, in drawing order
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layer {
+    Tunel,
+    Dividers,
+    Decor,
+    Streaks,
+    Player,
+    Bots,
+    Debris,
+    Bullets,
+}
+
+impl Layer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Tunel => "tunel",
+            Layer::Dividers => "dividers",
+            Layer::Decor => "decor",
+            Layer::Streaks => "streaks",
+            Layer::Player => "player",
+            Layer::Bots => "bots",
+            Layer::Debris => "debris",
+            Layer::Bullets => "bullets",
+        }
+    }
+}
+
+// Rendered shapes of a layer, faces are drawn before lines
+pub struct RenderedLayer {
+    pub layer: Layer,
+    pub primitives: DisplayList,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct World {
     pub tunel: Tunel,
     pub player: BoxCar,
     pub bots: Vec<Bot>,
     pub bullets: Vec<[Vector3<f64>; 3]>,
+    pub debris: Vec<Debris>,
+    pub wrecked: bool, // The player's car is destroyed, only its debris is left
+    pub speed: f64,    // Speed of the world toward the camera
+    pub distance: f64, // Distance travelled by the player
+    pub score: u64,    // Number of bots shot down
+    next_id: u64,      // Id of the next bot or debris
 }
 impl World {
     pub fn new(config: &GameConfig) -> World {
@@ -29,24 +81,179 @@ impl World {
         };
 
         World {
-            tunel: Tunel::new(&config),
+            tunel: Tunel::new(config),
             player,
             bots: Vec::new(),
             bullets: Vec::new(),
+            debris: Vec::new(),
+            wrecked: false,
+            speed: 0.,
+            distance: 0.,
+            score: 0,
+            next_id: 0,
         }
     }
 
-    pub fn render(&self, camera: &Camera) -> crate::Rendered {
-        Vec::new()
+    // Return the world between `prev` and `self` for rendering,
+    // `alpha` is the progress from `prev` to `self`, `dt` is the
+    // duration between them.
+    pub fn interpolate(&self, prev: &World, alpha: f64, dt: f64) -> World {
+        let lerp = |a: Vector3<f64>, b: Vector3<f64>| a + alpha * (b - a);
+        let mut world = self.clone();
+        world.tunel = self.tunel.interpolate(&prev.tunel, alpha);
+        world.distance = prev.distance + alpha * (self.distance - prev.distance);
+        world.player.position = lerp(prev.player.position, self.player.position);
+        for bot in &mut world.bots {
+            if let Some(old) = prev.bots.iter().find(|x| x.id == bot.id) {
+                bot.car.position = lerp(old.car.position, bot.car.position);
+            }
+        }
+        // Bullets move in straight lines, move them back along their
+        // direction.
+        for x in &mut world.bullets {
+            x[0] -= (1. - alpha) * dt * x[2];
+        }
+        for x in &mut world.debris {
+            if let Some(old) = prev.debris.iter().find(|y| y.id == x.id) {
+                *x = x.interpolate(old, alpha);
+            }
+        }
+        world
+    }
+
+    // Render the world as set in `config`, all layers in one list
+    pub fn render(&self, camera: &Camera, config: &GameConfig) -> DisplayList {
+        self.render_layers(camera, config)
             .into_iter()
-            .chain(self.tunel.render(camera))
-            .chain(self.player.render(camera))
-            .chain(self.bots.iter().flat_map(|x| x.render(camera)))
-            .chain(self.bullets_render(camera))
+            .flat_map(|x| x.primitives)
             .collect()
     }
+
+    // Render each layer separately, in drawing order, as set in
+    // `config`: with solid faces, the wireframe as overlay, lines
+    // hidden behind the player or bots and fog. With solid faces, the
+    // player and bots are drawn from the farthest to the nearest.
+    pub fn render_layers(&self, camera: &Camera, config: &GameConfig) -> Vec<RenderedLayer> {
+        let solid = config.solid_faces;
+        let wireframe = !solid || config.wireframe_overlay;
+        // Faces hide what is behind them anyway
+        let hidden_lines = match config.hidden_lines {
+            HiddenLines::Show if solid => HiddenLines::Hide,
+            x => x,
+        };
+        let player = if self.wrecked {
+            None
+        } else {
+            Some(&self.player)
+        };
+        let boxes: Vec<_> = match hidden_lines {
+            HiddenLines::Show => Vec::new(),
+            _ => player
+                .into_iter()
+                .chain(self.bots.iter().map(|x| &x.car))
+                .map(BoxCar::occluder)
+                .collect(),
+        };
+        let fog = config.fog.as_ref();
+        let colored = |mut lines: Vec<([Vector3<f64>; 2], Color)>| {
+            if let Some(fog) = fog {
+                lines = fog.lines(&lines, camera);
+            }
+            hidden::render(&lines, camera, &boxes, hidden_lines, LINE_WIDTH)
+        };
+        let lines = |lines: Vec<[Vector3<f64>; 2]>, color: Color| {
+            colored(lines.into_iter().map(|x| (x, color)).collect())
+        };
+        let wire = |x: Vec<[Vector3<f64>; 2]>, color: Color| {
+            if wireframe {
+                lines(x, color)
+            } else {
+                Vec::new()
+            }
+        };
+        let faces = |faces: Vec<Face>, color: Color, brightness: f64| {
+            if solid {
+                solid::render(&faces, camera, color, brightness, fog)
+            } else {
+                Vec::new()
+            }
+        };
+
+        let tunel = &self.tunel;
+        let mut ret: Vec<RenderedLayer> = Vec::new();
+        let mut push = |layer: Layer, faces: DisplayList, lines: DisplayList| {
+            let primitives = faces.into_iter().chain(lines);
+            match ret.last_mut() {
+                Some(last) if last.layer == layer => last.primitives.extend(primitives),
+                _ => ret.push(RenderedLayer {
+                    layer,
+                    primitives: primitives.collect(),
+                }),
+            }
+        };
+        push(
+            Layer::Tunel,
+            faces(tunel.faces(), tunel.color, TUNEL_BRIGHTNESS),
+            wire(tunel.edges(), tunel.color),
+        );
+        push(
+            Layer::Dividers,
+            faces(tunel.divider_faces(), tunel.color, 1.),
+            wire(tunel.dividers(), tunel.color),
+        );
+        push(Layer::Decor, Vec::new(), lines(tunel.decor(), tunel.color));
+        if let Some(x) = &config.speed_lines {
+            let streaks = x.lines(self.speed, self.distance, tunel.size);
+            push(
+                Layer::Streaks,
+                Vec::new(),
+                lines(streaks, tunel.color.mix(WHITE, STREAK_WHITENESS)),
+            );
+        }
+        let mut cars: Vec<_> = player
+            .map(|x| (Layer::Player, x))
+            .into_iter()
+            .chain(self.bots.iter().map(|x| (Layer::Bots, &x.car)))
+            .collect();
+        if solid {
+            let distance =
+                |x: &BoxCar| camera.depth(x.position + vec3(0., x.size.y, x.size.z) / 2.);
+            cars.sort_by(|a, b| {
+                distance(b.1)
+                    .partial_cmp(&distance(a.1))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        for (layer, car) in cars {
+            push(
+                layer,
+                faces(car.faces(), car.color, 1.),
+                wire(car.edges(), car.color),
+            );
+        }
+        // Debris fade out until `debris_time`
+        let debris = self
+            .debris
+            .iter()
+            .filter(|x| x.age < config.debris_time)
+            .flat_map(|x| {
+                let color = x.color.fade((1. - x.age / config.debris_time) as f32);
+                x.pieces.iter().map(move |p| (p.line(), color))
+            })
+            .collect();
+        push(Layer::Debris, Vec::new(), colored(debris));
+        let bullets = self.bullets.iter().map(|x| [x[0], x[0] + x[1]]).collect();
+        push(
+            Layer::Bullets,
+            Vec::new(),
+            lines(bullets, self.player.color),
+        );
+        ret
+    }
     pub fn update(&mut self, dt: f64, game_speed: f64) {
         let speed = game_speed + self.player.speed;
+        self.speed = speed;
+        self.distance += dt * speed;
         self.player.update_jump(dt);
         self.tunel.update(dt, speed);
         for x in &mut self.bots {
@@ -56,7 +263,41 @@ impl World {
         for x in &mut self.bullets {
             x[0] += dt * x[2];
         }
+        self.update_debris(dt, speed);
+    }
+
+    // Move the debris, `speed` is the speed of the world toward the
+    // camera
+    pub fn update_debris(&mut self, dt: f64, speed: f64) {
+        for x in &mut self.debris {
+            x.update(dt, speed, self.tunel.size);
+        }
+    }
+
+    // Remove debris older than `time`
+    pub fn expire_debris(&mut self, time: f64) {
+        self.debris.retain(|x| x.age < time);
+    }
+
+    // Break a car into debris
+    fn destroy(&mut self, car: &BoxCar, velocity: Vector3<f64>) {
+        self.debris.push(Debris::new(self.next_id, car, velocity));
+        self.next_id += 1;
+    }
+
+    // Break the player's car and the bots it crashed with
+    pub fn wreck(&mut self) {
+        let player = self.player.clone();
+        let (crashed, bots) = self.bots.drain(..).partition(|x| player.crashed(&x.car));
+        self.bots = bots;
+        for x in crashed {
+            self.destroy(&x.car, vec3(0., 0., -x.car.speed));
+        }
+        self.destroy(&player, vec3(0., 0., 0.));
+        self.wrecked = true;
+        self.speed = 0.;
     }
+
     pub fn validate(&mut self) {
         let size = self.tunel.size;
         self.bullets.retain(|x| {
@@ -80,8 +321,13 @@ impl World {
         }
 
         let bullets = self.bullets.clone();
-        self.bots
-            .retain(|x| x.pos().z > 0. && !bullets.iter().any(|b| x.hit(b)));
+        self.bots.retain(|x| x.pos().z > 0.);
+        let (mut destroyed, bots): (Vec<_>, Vec<_>) = self
+            .bots
+            .drain(..)
+            .partition(|x| bullets.iter().any(|b| x.hit(b)));
+        self.bots = bots;
+        self.score += destroyed.len() as u64;
         self.bots.sort_by(|a, b| {
             a.pos()
                 .z
@@ -96,30 +342,44 @@ impl World {
             .filter(|&(_, (x, y))| x.crashed(y))
             .map(|(i, _)| i)
             .collect::<::std::collections::BTreeSet<_>>();
-        self.bots = self
+        let (crashed, bots): (Vec<_>, Vec<_>) = self
             .bots
-            .iter()
+            .drain(..)
             .enumerate()
-            .filter(|&(i, _)| !((i > 0 && set.contains(&(i - 1))) || set.contains(&i)))
-            .map(|(_, x)| x.clone())
-            .collect();
+            .partition(|&(i, _)| (i > 0 && set.contains(&(i - 1))) || set.contains(&i));
+        self.bots = bots.into_iter().map(|(_, x)| x).collect();
+        destroyed.extend(crashed.into_iter().map(|(_, x)| x));
+        for x in destroyed {
+            self.destroy(&x.car, vec3(0., 0., -x.car.speed));
+        }
+    }
+    // Number of bots that passed the player since `prev`, closer than
+    // `distance` aside
+    pub fn near_misses(&self, prev: &World, distance: f64) -> usize {
+        let player = &self.player;
+        let front = |x: &BoxCar| x.position.z + x.size.z;
+        self.bots
+            .iter()
+            .filter(|bot| {
+                let passed = front(&bot.car) < player.position.z
+                    && prev
+                        .bots
+                        .iter()
+                        .any(|x| x.id == bot.id && front(&x.car) >= player.position.z);
+                let gap = (bot.car.position.x - player.position.x).abs()
+                    - (bot.car.size.x + player.size.x) / 2.;
+                passed && gap < distance
+            })
+            .count()
     }
-    pub fn add_bot(&mut self, rules: &BoxRules) {
-        self.bots.push(Bot::new_random(rules));
+    pub fn add_bot(&mut self, rules: &BoxRules, rng: &mut crate::GameRng) {
+        let mut bot = Bot::new_random(rules, rng);
+        bot.id = self.next_id;
+        self.next_id += 1;
+        self.bots.push(bot);
     }
     pub fn add_bullet(&mut self, origin: Vector3<f64>, direction: Vector3<f64>, len: f64) {
         self.bullets
             .push([origin, direction * len / direction.magnitude(), direction]);
     }
-
-    fn bullets_render(&self, camera: &Camera) -> crate::Rendered {
-        self.bullets
-            .iter()
-            .filter_map(|x| {
-                camera
-                    .render_line(&x[0], &(x[0] + x[1]))
-                    .map(|x| (x, self.player.color))
-            })
-            .collect()
-    }
 }
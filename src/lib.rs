@@ -0,0 +1,47 @@
+// Box Crash's game logic and rendering, without any window.
+//
+// `Simulation` runs the game from input commands, `World` holds every
+// object in the tunel and `Camera` projects them to a display list
+// of lines, polygons, circles and text that any backend can draw,
+// `raster` draws them without a GPU and `svg` exports them as SVG
+// documents.
+extern crate cgmath;
+extern crate conrod;
+extern crate image;
+extern crate rand;
+extern crate rusttype;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde;
+extern crate serde_json;
+
+pub mod color;
+pub mod display;
+pub mod game;
+pub mod raster;
+pub mod svg;
+
+pub use crate::game::{Camera, Car, GameConfig, Simulation, World};
+
+// Pixel present a point in the window and window's size
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pixel {
+    pub w: u32,
+    pub h: u32,
+}
+impl Pixel {
+    pub fn new(w: u32, h: u32) -> Pixel {
+        Pixel { w, h }
+    }
+}
+
+// Random number generator of the game, seeded so that a run can be
+// reproduced
+pub type GameRng = rand::prng::XorShiftRng;
+
+// Return a random number between a and b
+fn rnd<R: rand::Rng>(rng: &mut R, (a, b): (f64, f64)) -> f64 {
+    let (a, b) = (f64::min(a, b), f64::max(a, b));
+    // `gen::<f64>()` return a number between 0 and 1
+    rng.gen::<f64>() * (b - a) + a
+}
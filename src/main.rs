@@ -1,61 +1,84 @@
-extern crate cgmath;
 #[macro_use]
 extern crate conrod;
+extern crate boxcrash;
 extern crate piston_window;
-extern crate rand;
-#[macro_use]
-extern crate serde_derive;
-extern crate serde_json;
-
-type Rendered = Vec<([cgmath::Vector2<f64>; 2], color::Color)>;
 
-// Pixel present a point in the window and window's size
-#[derive(Clone, Copy, Serialize, Deserialize)]
-pub struct Pixel {
-    pub w: u32,
-    pub h: u32,
-}
-impl Pixel {
-    pub fn new(w: u32, h: u32) -> Pixel {
-        Pixel { w, h }
-    }
-}
-
-// Return a random number between a and b
-fn rnd((a, b): (f64, f64)) -> f64 {
-    let (a, b) = (f64::min(a, b), f64::max(a, b));
-    // `random::<f64>()` return a number between 0 and 1
-    rand::random::<f64>() * (b - a) + a
-}
-
-mod color;
+mod cli;
 mod conrod_helper;
 mod control;
-mod game;
+mod frontend;
 mod menu;
+mod watch;
 
 use crate::conrod_helper::ConrodUI;
 use crate::control::{EventHandler, Flow, State};
-use crate::game::GameConfig;
+use crate::frontend::Game;
 use crate::menu::*;
+use crate::watch::Watcher;
+use boxcrash::game::{save_field, user_config_path, Replay, Snapshot};
+use boxcrash::{GameConfig, Pixel, Simulation};
 use piston_window::*;
-use std::fs::File;
-use std::io::prelude::*;
 
 fn main() {
     // Wayland backend contains some bugs, prefer x11
     std::env::set_var("WINIT_UNIX_BACKEND", "x11");
 
-    // Try to read config file, fallback to the default config otherwise
-    let config: GameConfig = File::open("resources/config.json")
-        .ok()
-        .and_then(|mut f| {
-            let mut s = String::new();
-            f.read_to_string(&mut s)
-                .ok()
-                .and_then(|_| serde_json::from_str(&s).ok())
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::usage());
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::usage());
+        return;
+    }
+
+    // Refuse to run if any layer of the config has errors
+    let mut config = cli::load_config(&options).unwrap_or_else(|(code, errors)| {
+        for e in errors {
+            eprintln!("{}", e);
+        }
+        std::process::exit(code);
+    });
+
+    // A replay or a saved game brings its own config, `config` is
+    // kept for the next games
+    let replay = options.replay.as_ref().map(|path| {
+        Replay::load(path).unwrap_or_else(|e| {
+            eprintln!("Cannot load replay {}: {}", path.display(), e);
+            std::process::exit(1);
         })
-        .unwrap_or_default();
+    });
+    let snapshot = options.resume.as_ref().map(|path| {
+        Snapshot::load(path).unwrap_or_else(|e| {
+            eprintln!("Cannot load snapshot {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
+    // Render with the software rasterizer or to SVG, without a window
+    if let Some(path) = &options.screenshot {
+        let sim = match (replay, snapshot) {
+            (Some(replay), _) => replay.play(),
+            (None, Some(snapshot)) => Simulation::from_snapshot(snapshot),
+            (None, None) => Simulation::new(config),
+        };
+        let saved = if path.extension() == Some("svg".as_ref()) {
+            frontend::save_svg(&sim, 1., path)
+        } else {
+            frontend::screenshot(&sim, 1., None, path)
+        };
+        match saved {
+            Ok(()) => println!("Saved {}", path.display()),
+            Err(e) => {
+                eprintln!("Cannot save {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
     let size = config.screen_size;
 
@@ -73,11 +96,62 @@ fn main() {
 
     let mut start_menu: ConrodUI<StartMenu> = ConrodUI::new(size, &mut window, &mut ui);
     let mut play_again_menu: ConrodUI<PlayAgainMenu> = ConrodUI::new(size, &mut window, &mut ui);
-    let mut state = State::StartMenu;
+    let mut state = if options.play {
+        State::Playing
+    } else {
+        State::StartMenu
+    };
+
+    let mut game = match (replay, snapshot) {
+        (Some(replay), _) => {
+            state = State::Playing;
+            Game::from_replay(replay, &window)
+        }
+        (None, Some(snapshot)) => {
+            state = State::Playing;
+            Game::from_snapshot(snapshot, &window)
+        }
+        (None, None) => Game::new(config.clone(), &window),
+    };
 
-    let mut game = game::Game::new(config.clone(), &window);
+    // Config files are watched, changes apply to the running game
+    let files = cli::config_files(&options);
+    let mut watcher = Watcher::new(files.into_iter().map(|(x, _)| x).collect());
+    // Resolution chosen in a menu, it overrides the command line's for
+    // the rest of the session
+    let mut resolution = None;
 
     while let Some(event) = window.next() {
+        if watcher.changed() {
+            match cli::load_config(&options) {
+                Ok(mut new) => {
+                    if let Some(size) = resolution {
+                        new.screen_size = size;
+                    }
+                    window.set_max_fps(new.max_fps);
+                    if new.screen_size != config.screen_size {
+                        window.set_size([new.screen_size.w, new.screen_size.h]);
+                    }
+                    for field in game.reconfigure(new.clone()) {
+                        println!("`{}` can't change during a game, it is not applied", field);
+                    }
+                    // The window keeps its settings for the next games
+                    config = GameConfig {
+                        title: config.title.clone(),
+                        ups: config.ups,
+                        ..new
+                    };
+                    println!("Config reloaded");
+                }
+                Err((_, errors)) => {
+                    for e in errors {
+                        eprintln!("{}", e);
+                    }
+                    eprintln!("Config not reloaded");
+                }
+            }
+        }
+
         let flow = match state {
             State::StartMenu => start_menu.handle_event(event, &mut window, &mut ui),
             State::Playing => game.handle_event(event, &mut window, &mut ()),
@@ -91,9 +165,37 @@ fn main() {
                 LoseGame => state = State::PlayAgainMenu,
                 PlayAgain => {
                     state = State::Playing;
-                    game = game::Game::new(config.clone(), &window);
+                    game = Game::new(config.clone(), &window);
+                }
+                SetResolution(size) => {
+                    resolution = Some(size);
+                    window.set_size([size.w, size.h]);
+                    config.screen_size = size;
+                    save_resolution(size);
                 }
             }
         }
     }
 }
+
+// Save the window's size to the user's config file, it is used from
+// the next start on
+fn save_resolution(size: Pixel) {
+    let path = match user_config_path() {
+        Some(x) => x,
+        None => {
+            eprintln!("Cannot save the resolution: no user config directory");
+            return;
+        }
+    };
+    let value = serde_json::json!({ "w": size.w, "h": size.h });
+    match save_field(&path, "screen_size", value) {
+        Ok(()) => println!(
+            "Saved resolution {}x{} to {}",
+            size.w,
+            size.h,
+            path.display()
+        ),
+        Err(e) => eprintln!("Cannot save {}: {}", path.display(), e),
+    }
+}
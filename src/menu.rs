@@ -3,15 +3,50 @@ use conrod::UiCell;
 
 use crate::conrod_helper::Gui;
 use crate::control::Flow;
+use boxcrash::Pixel;
 
 widget_ids! {
     pub struct Ids {
         canvas,
         button,
         text,
+        resolution,
     }
 }
 
+// Window sizes offered in the menus
+const RESOLUTIONS: [(u32, u32); 6] = [
+    (800, 600),
+    (1024, 768),
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+];
+
+// A list to choose the window's size, below the menu's button. A size
+// set by resizing the window is shown but isn't in the list.
+fn resolution(ui: &mut UiCell, ids: &Ids, size: Pixel) -> Option<Flow> {
+    use conrod::{Labelable, Positionable, Sizeable, Widget};
+
+    let items: Vec<_> = RESOLUTIONS
+        .iter()
+        .map(|&(w, h)| format!("{}x{}", w, h))
+        .collect();
+    let selected = RESOLUTIONS
+        .iter()
+        .position(|&(w, h)| Pixel::new(w, h) == size);
+    let label = format!("{}x{}", size.w, size.h);
+    widget::DropDownList::new(&items, selected)
+        .label(&label)
+        .align_middle_x_of(ids.canvas)
+        .down_from(ids.button, 20.0)
+        .w_h(120.0, 30.0)
+        .set(ids.resolution, ui)
+        .filter(|&i| Some(i) != selected)
+        .map(|i| Flow::SetResolution(Pixel::new(RESOLUTIONS[i].0, RESOLUTIONS[i].1)))
+}
+
 pub struct StartMenu;
 
 impl Gui for StartMenu {
@@ -25,7 +60,7 @@ impl Gui for StartMenu {
         Self::Ids::new(w)
     }
 
-    fn gui(&mut self, ui: &mut UiCell, ids: &Self::Ids) -> Option<Flow> {
+    fn gui(&mut self, ui: &mut UiCell, ids: &Self::Ids, size: Pixel) -> Option<Flow> {
         use conrod::{Labelable, Positionable, Sizeable, Widget};
 
         widget::Canvas::new().set(ids.canvas, ui);
@@ -36,10 +71,12 @@ impl Gui for StartMenu {
             .w_h(120.0, 30.0)
             .set(ids.button, ui);
 
+        let flow = resolution(ui, ids, size);
+
         if button.next().is_some() {
             Some(Flow::StartGame)
         } else {
-            None
+            flow
         }
     }
 }
@@ -57,7 +94,7 @@ impl Gui for PlayAgainMenu {
         Self::Ids::new(w)
     }
 
-    fn gui(&mut self, ui: &mut UiCell, ids: &Self::Ids) -> Option<Flow> {
+    fn gui(&mut self, ui: &mut UiCell, ids: &Self::Ids, size: Pixel) -> Option<Flow> {
         use conrod::{Labelable, Positionable, Sizeable, Widget};
 
         widget::Canvas::new().set(ids.canvas, ui);
@@ -73,10 +110,12 @@ impl Gui for PlayAgainMenu {
             .up_from(ids.button, 30.0)
             .set(ids.text, ui);
 
+        let flow = resolution(ui, ids, size);
+
         if button.next().is_some() {
             Some(Flow::PlayAgain)
         } else {
-            None
+            flow
         }
     }
 }
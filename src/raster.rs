@@ -0,0 +1,260 @@
+// A software rasterizer: draws display lists into an RGBA image in
+// memory, so frames can be saved without a GPU.
+use crate::color::Color;
+use crate::display::{clip, dashes, Primitive, Shape, Stroke};
+use crate::game::RenderedLayer;
+use cgmath::prelude::*;
+use cgmath::{vec2, Vector2};
+use image::RgbaImage;
+use rusttype::{point, Font, FontCollection, Scale};
+
+use std::io;
+use std::path::Path;
+
+pub struct Canvas {
+    image: RgbaImage,
+    font: Option<Font<'static>>, // Font of text primitives, they are skipped without it
+}
+
+// Read a TrueType font to draw text with
+pub fn load_font<P: AsRef<Path>>(path: P) -> io::Result<Font<'static>> {
+    let bytes = std::fs::read(path)?;
+    FontCollection::from_bytes(bytes)
+        .and_then(|x| x.into_font())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+// Distance from `p` to the segment `a`-`b`
+fn distance(p: Vector2<f64>, a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    let d = b - a;
+    let t = if d.magnitude2() > 0. {
+        ((p - a).dot(d) / d.magnitude2()).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (a + t * d - p).magnitude()
+}
+
+impl Canvas {
+    pub fn new(size: crate::Pixel, background: Color) -> Canvas {
+        let mut canvas = Canvas {
+            image: RgbaImage::new(size.w, size.h),
+            font: None,
+        };
+        canvas.rectangle(
+            [0., 0., f64::from(size.w), f64::from(size.h)],
+            background.alpha(1.),
+        );
+        canvas
+    }
+
+    pub fn set_font(&mut self, font: Font<'static>) {
+        self.font = Some(font);
+    }
+
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.image.save(path)
+    }
+
+    // Blend `color` over pixel (x, y), `cover` is the part of the
+    // pixel covered, between 0 and 1.
+    fn blend(&mut self, x: i64, y: i64, color: Color, cover: f64) {
+        if x < 0
+            || y < 0
+            || x >= i64::from(self.image.width())
+            || y >= i64::from(self.image.height())
+        {
+            return;
+        }
+        let c: [f32; 4] = color.into();
+        let a = f64::from(c[3]) * cover;
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        for (x, &new) in pixel.data.iter_mut().zip(&c).take(3) {
+            let (old, new) = (f64::from(*x), f64::from(new) * 255.);
+            *x = (old + (new - old) * a).round() as u8;
+        }
+        let old = f64::from(pixel.data[3]) / 255.;
+        pixel.data[3] = ((old + (1. - old) * a) * 255.).round() as u8;
+    }
+
+    // Draw an anti-aliased line, `radius` is half of its width
+    pub fn line(&mut self, [a, b]: [Vector2<f64>; 2], radius: f64, color: Color) {
+        // Lines can reach far outside of the screen
+        let reach = radius + 1.;
+        let size = vec2(
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        let [a, b] = match clip(a, b, vec2(-reach, -reach), size + vec2(reach, reach)) {
+            Some(x) => x,
+            None => return,
+        };
+        // Walk along the major axis, `steep` lines have y as major axis
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        let swap = |p: Vector2<f64>| if steep { vec2(p.y, p.x) } else { p };
+        let (a, b) = (swap(a), swap(b));
+        let (a, b) = if a.x > b.x { (b, a) } else { (a, b) };
+        let minor = reach * 1.5;
+        for x in (a.x - reach).floor() as i64..=(b.x + reach).ceil() as i64 {
+            let px = x as f64 + 0.5;
+            let t = if b.x > a.x {
+                ((px - a.x) / (b.x - a.x)).clamp(0., 1.)
+            } else {
+                0.
+            };
+            let cy = a.y + t * (b.y - a.y);
+            for y in (cy - minor).floor() as i64..=(cy + minor).ceil() as i64 {
+                let p = vec2(px, y as f64 + 0.5);
+                let cover = (radius + 0.5 - distance(p, a, b)).clamp(0., 1.);
+                if cover > 0. {
+                    let (x, y) = if steep { (y, x) } else { (x, y) };
+                    self.blend(x, y, color, cover);
+                }
+            }
+        }
+    }
+
+    // Fill a rectangle given as [x, y, width, height]
+    pub fn rectangle(&mut self, [x, y, w, h]: [f64; 4], color: Color) {
+        let (x0, x1) = (x.min(x + w).round() as i64, x.max(x + w).round() as i64);
+        let (y0, y1) = (y.min(y + h).round() as i64, y.max(y + h).round() as i64);
+        let (x0, y0) = (x0.max(0), y0.max(0));
+        let x1 = x1.min(i64::from(self.image.width()));
+        let y1 = y1.min(i64::from(self.image.height()));
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.blend(x, y, color, 1.);
+            }
+        }
+    }
+
+    // Fill a polygon, pixels are filled if their centre is inside
+    pub fn polygon(&mut self, points: &[Vector2<f64>], color: Color) {
+        let top = points.iter().fold(f64::INFINITY, |a, p| a.min(p.y));
+        let bottom = points.iter().fold(f64::NEG_INFINITY, |a, p| a.max(p.y));
+        let top = top.floor().max(0.) as i64;
+        let bottom = bottom.ceil().min(f64::from(self.image.height())) as i64;
+        let width = f64::from(self.image.width());
+        for y in top..bottom {
+            let py = y as f64 + 0.5;
+            // Where the row crosses the polygon's edges
+            let mut xs: Vec<f64> = points
+                .iter()
+                .zip(points.iter().cycle().skip(1))
+                .filter(|(a, b)| (a.y <= py) != (b.y <= py))
+                .map(|(a, b)| a.x + (py - a.y) / (b.y - a.y) * (b.x - a.x))
+                .collect();
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            for x in xs.chunks(2).filter(|x| x.len() == 2) {
+                let start = (x[0] - 0.5).ceil().max(0.) as i64;
+                let end = (x[1] - 0.5).floor().min(width - 1.) as i64;
+                for x in start..=end {
+                    self.blend(x, y, color, 1.);
+                }
+            }
+        }
+    }
+
+    // Fill a circle, anti-aliased
+    pub fn disc(&mut self, centre: Vector2<f64>, radius: f64, color: Color) {
+        let reach = radius + 1.;
+        for y in (centre.y - reach).floor() as i64..=(centre.y + reach).ceil() as i64 {
+            for x in (centre.x - reach).floor() as i64..=(centre.x + reach).ceil() as i64 {
+                let p = vec2(x as f64 + 0.5, y as f64 + 0.5);
+                let cover = (radius + 0.5 - (p - centre).magnitude()).clamp(0., 1.);
+                if cover > 0. {
+                    self.blend(x, y, color, cover);
+                }
+            }
+        }
+    }
+
+    // Draw the outline of a circle, `border` is the outline's width
+    pub fn circle(&mut self, centre: Vector2<f64>, radius: f64, border: f64, color: Color) {
+        let reach = radius + border + 1.;
+        for y in (centre.y - reach).floor() as i64..=(centre.y + reach).ceil() as i64 {
+            for x in (centre.x - reach).floor() as i64..=(centre.x + reach).ceil() as i64 {
+                let p = vec2(x as f64 + 0.5, y as f64 + 0.5);
+                let d = ((p - centre).magnitude() - radius).abs();
+                let cover = (border / 2. + 0.5 - d).clamp(0., 1.);
+                if cover > 0. {
+                    self.blend(x, y, color, cover);
+                }
+            }
+        }
+    }
+
+    // Draw text with its baseline starting at `pos`, `size` is the
+    // font's height in pixels.
+    pub fn text(&mut self, font: &Font, size: f64, pos: Vector2<f64>, text: &str, color: Color) {
+        let scale = Scale::uniform(size as f32);
+        let start = point(pos.x as f32, pos.y as f32);
+        for glyph in font.layout(text, scale, start) {
+            if let Some(bb) = glyph.pixel_bounding_box() {
+                glyph.draw(|x, y, cover| {
+                    let x = i64::from(bb.min.x) + i64::from(x);
+                    let y = i64::from(bb.min.y) + i64::from(y);
+                    self.blend(x, y, color, f64::from(cover));
+                });
+            }
+        }
+    }
+
+    // Draw a display list, in order
+    pub fn draw(&mut self, list: &[Primitive]) {
+        let size = vec2(
+            f64::from(self.image.width()),
+            f64::from(self.image.height()),
+        );
+        for x in list {
+            match &x.shape {
+                Shape::Line {
+                    points,
+                    width,
+                    stroke: Stroke::Solid,
+                } => self.line(*points, width / 2., x.color),
+                Shape::Line {
+                    points,
+                    width,
+                    stroke: Stroke::Dashed { dash, gap },
+                } => {
+                    for l in dashes(*points, *dash, *gap, vec2(0., 0.), size) {
+                        self.line(l, width / 2., x.color);
+                    }
+                }
+                Shape::Polygon(points) => self.polygon(points, x.color),
+                Shape::Circle {
+                    centre,
+                    radius,
+                    outline: Some(width),
+                } => self.circle(*centre, *radius, *width, x.color),
+                Shape::Circle {
+                    centre,
+                    radius,
+                    outline: None,
+                } => self.disc(*centre, *radius, x.color),
+                Shape::Text { pos, size, text } => {
+                    if let Some(font) = self.font.take() {
+                        self.text(&font, *size, *pos, text, x.color);
+                        self.font = Some(font);
+                    }
+                }
+            }
+        }
+    }
+
+    // Draw rendered layers, in order
+    pub fn draw_layers(&mut self, layers: &[RenderedLayer]) {
+        for x in layers {
+            self.draw(&x.primitives);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Export rendered frames as SVG documents, shapes are grouped by the
+// layer they come from.
+use crate::color::Color;
+use crate::display::{clip, Primitive, Shape, Stroke};
+use crate::game::RenderedLayer;
+use cgmath::vec2;
+
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+// Return `color` as an SVG color and its opacity
+fn paint(color: Color) -> (String, f32) {
+    let c: [f32; 4] = color.into();
+    let x = |x: f32| (x.clamp(0., 1.) * 255.).round() as u8;
+    (
+        format!("#{:02x}{:02x}{:02x}", x(c[0]), x(c[1]), x(c[2])),
+        c[3],
+    )
+}
+
+// Escape text to put in an SVG document
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Write a shape as an SVG element, lines are clipped to the frame's
+// size `(w, h)`.
+fn element(svg: &mut String, x: &Primitive, (w, h): (f64, f64)) {
+    let (color, opacity) = paint(x.color);
+    let _ = match &x.shape {
+        Shape::Line {
+            points,
+            width,
+            stroke,
+        } => {
+            let [a, b] = match clip(points[0], points[1], vec2(0., 0.), vec2(w, h)) {
+                Some(x) => x,
+                None => return,
+            };
+            let dash = match stroke {
+                Stroke::Solid => String::new(),
+                Stroke::Dashed { dash, gap } => {
+                    format!(
+                        r#" stroke-dasharray="{} {}" stroke-linecap="butt""#,
+                        dash, gap
+                    )
+                }
+            };
+            writeln!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}" stroke-opacity="{}" stroke-width="{}"{}/>"#,
+                a.x, a.y, b.x, b.y, color, opacity, width, dash
+            )
+        }
+        Shape::Polygon(points) => {
+            let points: Vec<_> = points
+                .iter()
+                .map(|p| format!("{:.2},{:.2}", p.x, p.y))
+                .collect();
+            writeln!(
+                svg,
+                r#"<polygon points="{}" fill="{}" fill-opacity="{}" stroke="none"/>"#,
+                points.join(" "),
+                color,
+                opacity
+            )
+        }
+        Shape::Circle {
+            centre,
+            radius,
+            outline: Some(width),
+        } => writeln!(
+            svg,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="none" stroke="{}" stroke-opacity="{}" stroke-width="{}"/>"#,
+            centre.x, centre.y, radius, color, opacity, width
+        ),
+        Shape::Circle {
+            centre,
+            radius,
+            outline: None,
+        } => writeln!(
+            svg,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}" fill-opacity="{}" stroke="none"/>"#,
+            centre.x, centre.y, radius, color, opacity
+        ),
+        Shape::Text { pos, size, text } => writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" font-size="{}" font-family="sans-serif" fill="{}" fill-opacity="{}">{}</text>"#,
+            pos.x,
+            pos.y,
+            size,
+            color,
+            opacity,
+            escape(text)
+        ),
+    };
+}
+
+// Return a frame as an SVG document of `size`. Layers are drawn in
+// order, a layer can appear in more than one group when solid boxes
+// are sorted by depth.
+pub fn frame(size: crate::Pixel, background: Color, layers: &[RenderedLayer]) -> String {
+    let (w, h) = (f64::from(size.w), f64::from(size.h));
+    let mut svg = String::new();
+    // Writing to a `String` never fails
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        size.w, size.h, size.w, size.h
+    );
+    let (fill, opacity) = paint(background);
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}" fill-opacity="{}"/>"#,
+        fill, opacity
+    );
+    for layer in layers {
+        let _ = writeln!(
+            svg,
+            r#"<g class="{}" stroke-linecap="round">"#,
+            layer.layer.name()
+        );
+        for x in &layer.primitives {
+            element(&mut svg, x, (w, h));
+        }
+        svg.push_str("</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    size: crate::Pixel,
+    background: Color,
+    layers: &[RenderedLayer],
+) -> io::Result<()> {
+    std::fs::write(path, frame(size, background, layers))
+}
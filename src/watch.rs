@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// Time between two checks of the files
+const INTERVAL: Duration = Duration::from_millis(500);
+
+// Watch files for changes by polling their modified times
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>, // Files with their last modified time
+    last_check: Instant,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+impl Watcher {
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        Watcher {
+            files: paths
+                .into_iter()
+                .map(|x| {
+                    let time = modified(&x);
+                    (x, time)
+                })
+                .collect(),
+            last_check: Instant::now(),
+        }
+    }
+
+    // Return true if a file was changed, created or removed since the
+    // last check. Files are checked at most once per `INTERVAL`.
+    pub fn changed(&mut self) -> bool {
+        if self.last_check.elapsed() < INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let mut changed = false;
+        for (path, time) in &mut self.files {
+            let new = modified(path);
+            if new != *time {
+                *time = new;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,62 @@
+use boxcrash::game::{Fov, Projection};
+use boxcrash::{Camera, Pixel};
+use cgmath::{vec3, Vector2};
+
+// A camera at the origin, looking down the z axis
+fn camera() -> Camera {
+    let projection = Projection::Perspective(Fov::Horizontal(90.));
+    let mut camera = Camera::new(Pixel::new(200, 100), vec3(0., 0., 0.), projection);
+    camera.look_at(vec3(0., 0., 0.), vec3(0., 0., 1.));
+    camera
+}
+
+fn finite(points: &[Vector2<f64>]) -> bool {
+    points.iter().all(|p| p.x.is_finite() && p.y.is_finite())
+}
+
+#[test]
+fn lines_behind_the_camera_are_clipped() {
+    let camera = camera();
+    assert!(camera
+        .render_line(&vec3(0., 0., -5.), &vec3(1., 1., -1.))
+        .is_none());
+    assert!(camera
+        .render_line(&vec3(0., 0., 0.), &vec3(0., 0., 0.))
+        .is_none());
+    // From behind the camera to in front of it
+    let line = camera.render_line(&vec3(-1., 0., -5.), &vec3(1., 0., 5.));
+    assert!(finite(&line.unwrap()));
+}
+
+#[test]
+fn lines_beside_the_camera_are_clipped() {
+    let camera = camera();
+    assert!(camera
+        .render_line(&vec3(-10., 0., 1e-9), &vec3(10., 0., 1e-9))
+        .is_none());
+    assert!(camera
+        .render_line(&vec3(5., 0., 1.), &vec3(5., 3., 2.))
+        .is_none());
+    let line = camera.render_line(&vec3(-1e6, 0., 1.), &vec3(1e6, 0., 1e3));
+    assert!(finite(&line.unwrap()));
+}
+
+#[test]
+fn polygons_around_the_camera_are_clipped() {
+    let camera = camera();
+    let behind = [
+        vec3(-1., -1., -1.),
+        vec3(1., -1., -1.),
+        vec3(1., 1., -1.),
+        vec3(-1., 1., -1.),
+    ];
+    assert!(camera.render_polygon(&behind).is_none());
+    // A floor under the camera, from behind it to far ahead
+    let floor = [
+        vec3(-10., -1., -10.),
+        vec3(10., -1., -10.),
+        vec3(10., -1., 10.),
+        vec3(-10., -1., 10.),
+    ];
+    assert!(finite(&camera.render_polygon(&floor).unwrap()));
+}
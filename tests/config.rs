@@ -0,0 +1,117 @@
+use boxcrash::game::{ConfigError, LayeredConfig};
+use boxcrash::GameConfig;
+
+#[test]
+fn nested_field_overrides_alone() {
+    let mut layers = LayeredConfig::new();
+    layers
+        .add_json(
+            "user",
+            r#"{"screen_size": {"w": 1280}, "fog": {"start": 10.0}}"#,
+        )
+        .unwrap();
+    let config = layers.build().unwrap();
+    let default = GameConfig::default();
+    assert_eq!(config.screen_size.w, 1280);
+    assert_eq!(config.screen_size.h, default.screen_size.h);
+    let (fog, default_fog) = (config.fog.unwrap(), default.fog.unwrap());
+    assert_eq!(fog.start, 10.);
+    assert_eq!(fog.end, default_fog.end);
+}
+
+#[test]
+fn invalid_field_is_named_with_its_line() {
+    let mut layers = LayeredConfig::new();
+    layers
+        .add_json("user", "{\n    \"title\": \"Box\",\n    \"ups\": 0\n}")
+        .unwrap();
+    let errors = match layers.build() {
+        Ok(_) => panic!("`ups` of 0 was accepted"),
+        Err(x) => x,
+    };
+    assert_eq!(errors.len(), 1);
+    let (layer, error) = &errors[0];
+    assert_eq!(layer, "user");
+    match error {
+        ConfigError::Invalid { field, line, .. } => {
+            assert_eq!(*field, "ups");
+            assert_eq!(*line, Some(3));
+        }
+        _ => panic!("expected an invalid field, got {}", error),
+    }
+    assert!(error.to_string().contains("line 3: `ups`"));
+}
+
+#[test]
+fn wrong_type_is_named_with_its_line() {
+    let errors = LayeredConfig::new()
+        .add_json("user", "{\n    \"ups\": 60,\n    \"fog\": 3\n}")
+        .unwrap_err();
+    assert_eq!(errors.len(), 1);
+    match &errors[0] {
+        ConfigError::Parse { line, message, .. } => {
+            assert_eq!(*line, 3);
+            assert!(message.contains("`fog`"));
+        }
+        x => panic!("expected a parse error, got {}", x),
+    }
+}
+
+#[test]
+fn later_layers_override_earlier_ones() {
+    let mut layers = LayeredConfig::new();
+    layers
+        .add_json("shipped", r#"{"ups": 30, "max_fps": 30}"#)
+        .unwrap();
+    layers.add_json("user", r#"{"ups": 90}"#).unwrap();
+    let config = layers.build().unwrap();
+    assert_eq!(config.ups, 90);
+    assert_eq!(config.max_fps, 30);
+
+    layers.set("command line", "ups", "120").unwrap();
+    assert_eq!(layers.build().unwrap().ups, 120);
+}
+
+// Fields reported as invalid in a config made of `source`
+fn invalid_fields(source: &str) -> Vec<&'static str> {
+    let mut layers = LayeredConfig::new();
+    layers.add_json("user", source).unwrap();
+    match layers.build() {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors
+            .into_iter()
+            .filter_map(|(_, e)| match e {
+                ConfigError::Invalid { field, .. } => Some(field),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn lengths_must_be_positive() {
+    assert_eq!(
+        invalid_fields(r#"{"decor_distance": 0.0}"#),
+        ["decor_distance"]
+    );
+    assert_eq!(
+        invalid_fields(r#"{"decor_distance": -3.0}"#),
+        ["decor_distance"]
+    );
+    assert_eq!(
+        invalid_fields(r#"{"divider_size": [0.0, 5.0]}"#),
+        ["divider_size"]
+    );
+    assert_eq!(
+        invalid_fields(r#"{"divider_size": [1.0, 0.0]}"#),
+        ["divider_size"]
+    );
+    assert_eq!(
+        invalid_fields(r#"{"screen_size": {"w": 0}}"#),
+        ["screen_size"]
+    );
+    assert_eq!(
+        invalid_fields(r#"{"screen_size": {"h": 0}}"#),
+        ["screen_size"]
+    );
+}
@@ -0,0 +1,48 @@
+use boxcrash::color::RED;
+use boxcrash::game::{Debris, Piece};
+use cgmath::vec3;
+
+fn falling(velocity: f64) -> Debris {
+    Debris {
+        id: 0,
+        pieces: vec![Piece {
+            centre: vec3(5., 0.1, 10.),
+            half: vec3(0.5, 0., 0.),
+            velocity: vec3(1., velocity, 2.),
+            axis: vec3(0., 1., 0.),
+            spin: 0.,
+        }],
+        color: RED,
+        age: 0.,
+    }
+}
+
+#[test]
+fn pieces_bounce_off_the_road() {
+    let size = vec3(15., 8., 150.);
+    let mut debris = falling(-10.);
+    debris.update(0.01, 0., size);
+    let piece = &debris.pieces[0];
+    assert_eq!(piece.centre.y, 0.);
+    // Slower up than it fell, slowed along the road
+    assert!(piece.velocity.y > 0. && piece.velocity.y < 10.);
+    assert!(piece.velocity.x < 1. && piece.velocity.z < 2.);
+}
+
+#[test]
+fn pieces_stay_inside_the_tunel() {
+    let size = vec3(15., 8., 150.);
+    let mut debris = falling(40.);
+    debris.pieces[0].velocity.x = -30.;
+    debris.pieces[0].spin = 5.;
+    for _ in 0..300 {
+        debris.update(0.01, 0., size);
+        for [a, b] in debris.pieces.iter().map(|x| x.line()) {
+            for p in &[a, b] {
+                assert!(p.x >= -1e-9 && p.x <= size.x + 1e-9);
+                assert!(p.y >= -1e-9 && p.y <= size.y + 1e-9);
+            }
+        }
+    }
+    assert!((debris.age - 3.).abs() < 1e-9);
+}
@@ -0,0 +1,65 @@
+use boxcrash::color::*;
+use boxcrash::game::{Face, Fog, Fov, Projection};
+use boxcrash::{Camera, Pixel};
+use cgmath::vec3;
+
+// A camera at the origin looking down the z axis, in a fog from 10
+// to 50
+fn scene() -> (Camera, Fog) {
+    let projection = Projection::Perspective(Fov::Horizontal(90.));
+    let camera = Camera::new(Pixel::new(200, 100), vec3(0., 0., 0.), projection);
+    let fog = Fog {
+        color: BLACK,
+        start: 10.,
+        end: 50.,
+    };
+    (camera, fog)
+}
+
+#[test]
+fn lines_fade_with_depth() {
+    let (camera, fog) = scene();
+    let (a, b) = (vec3(0., -1., 1.), vec3(0., -1., 60.));
+    let pieces = fog.lines(&[([a, b], WHITE)], &camera);
+    assert!(pieces.len() > 1);
+    assert_eq!(pieces[0].0[0], a);
+    assert_eq!(pieces[pieces.len() - 1].0[1], b);
+    for (x, y) in pieces.iter().zip(pieces.iter().skip(1)) {
+        assert_eq!(x.0[1], y.0[0]);
+    }
+    let red = |x: &([_; 2], Color)| <[f32; 4]>::from(x.1)[0];
+    // Unchanged before the fog starts, only fog after it ends
+    assert_eq!(red(&pieces[0]), 1.);
+    assert_eq!(red(&pieces[pieces.len() - 1]), 0.);
+    assert!(pieces.windows(2).all(|x| red(&x[0]) >= red(&x[1])));
+}
+
+#[test]
+fn lines_outside_the_fog_are_kept_whole() {
+    let (camera, fog) = scene();
+    let line = [vec3(-5., 0., 2.), vec3(5., 0., 8.)];
+    assert_eq!(fog.lines(&[(line, WHITE)], &camera).len(), 1);
+}
+
+#[test]
+fn faces_are_cut_in_strips_along_depth() {
+    let (camera, fog) = scene();
+    let face = Face::new(
+        [
+            vec3(-1., -1., 0.),
+            vec3(1., -1., 0.),
+            vec3(1., -1., 60.),
+            vec3(-1., -1., 60.),
+        ],
+        vec3(0., 1., 0.),
+    );
+    let strips = fog.cut(&face, &camera);
+    assert!(strips.len() > 1);
+    let near = |x: &Face| x.points[0].z;
+    let far = |x: &Face| x.points[1].z;
+    assert_eq!(near(&strips[0]), 0.);
+    assert_eq!(far(&strips[strips.len() - 1]), 60.);
+    for (x, y) in strips.iter().zip(strips.iter().skip(1)) {
+        assert_eq!(far(x), near(y));
+    }
+}
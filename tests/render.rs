@@ -0,0 +1,59 @@
+use boxcrash::color::*;
+use boxcrash::display::Primitive;
+use boxcrash::raster::Canvas;
+use boxcrash::{GameConfig, Pixel, Simulation};
+use cgmath::vec2;
+use image::RgbaImage;
+
+use std::path::Path;
+
+// Compare `actual` with the image `name` in tests/golden, a different
+// image is saved to the temporary directory to look at.
+fn check_golden(actual: RgbaImage, name: &str) {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    let expected = image::open(&golden).unwrap().to_rgba();
+    if actual.dimensions() != expected.dimensions()
+        || actual.clone().into_raw() != expected.into_raw()
+    {
+        let path = std::env::temp_dir().join(format!("boxcrash-{}", name));
+        actual.save(&path).unwrap();
+        panic!(
+            "image differs from {}, see {}",
+            golden.display(),
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn canvas_matches_golden_image() {
+    let mut canvas = Canvas::new(Pixel::new(16, 12), BLACK);
+    canvas.draw(&[
+        Primitive::rectangle([1., 1., 6., 4.], BLUE),
+        Primitive::polygon(
+            vec![vec2(9., 1.), vec2(15., 1.), vec2(12., 6.)],
+            GREEN.alpha(0.5),
+        ),
+        Primitive::line([vec2(1., 10.), vec2(15., 7.)], 1.5, WHITE),
+        Primitive::circle(vec2(4., 8.), 2.5, None, RED),
+        Primitive::circle(vec2(12., 9.), 2., Some(1.), YELLOW),
+    ]);
+    check_golden(canvas.into_image(), "canvas.png");
+}
+
+#[test]
+fn game_matches_golden_image() {
+    let mut sim = Simulation::new(GameConfig {
+        seed: Some(3),
+        screen_size: Pixel::new(160, 120),
+        ..GameConfig::default()
+    });
+    for _ in 0..240 {
+        sim.step();
+    }
+    let mut canvas = Canvas::new(sim.config.screen_size, BLACK);
+    canvas.draw(&sim.render(1.));
+    check_golden(canvas.into_image(), "game.png");
+}
@@ -0,0 +1,126 @@
+use boxcrash::game::{Command, Control, Replay, Snapshot};
+use boxcrash::{GameConfig, Simulation};
+
+use std::path::PathBuf;
+
+fn seeded(seed: u64) -> Simulation {
+    Simulation::new(GameConfig {
+        seed: Some(seed),
+        ..GameConfig::default()
+    })
+}
+
+// Play `ticks` updates with some steering, jumping and shooting
+fn run(sim: &mut Simulation, ticks: u64) {
+    for _ in 0..ticks {
+        if sim.ended() {
+            return;
+        }
+        match sim.ticks() % 120 {
+            10 => sim.apply(Command::Press(Control::Left)),
+            40 => sim.apply(Command::Release(Control::Left)),
+            50 => sim.apply(Command::Press(Control::Shoot)),
+            60 => sim.apply(Command::Press(Control::Jump)),
+            70 => sim.apply(Command::Press(Control::Right)),
+            100 => sim.apply(Command::Release(Control::Right)),
+            _ => (),
+        }
+        sim.step();
+    }
+}
+
+// A file in the temporary directory, unique to this test process
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("boxcrash-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn same_seed_same_bots() {
+    let (mut a, mut b) = (seeded(7), seeded(7));
+    run(&mut a, 300);
+    run(&mut b, 300);
+    let (a, b) = (a.frame(1.).world, b.frame(1.).world);
+    assert!(!a.bots.is_empty());
+    assert_eq!(a.bots.len(), b.bots.len());
+    for (a, b) in a.bots.iter().zip(&b.bots) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.car.position, b.car.position);
+        assert_eq!(a.car.size, b.car.size);
+    }
+}
+
+#[test]
+fn replay_plays_back_the_run() {
+    let mut sim = seeded(11);
+    run(&mut sim, 400);
+    let path = temp_file("replay.json");
+    sim.replay().save(&path).unwrap();
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let played = replay.play();
+    assert_eq!(played.ticks(), sim.ticks());
+    let (a, b) = (played.frame(1.).world, sim.frame(1.).world);
+    assert_eq!(a.player.position, b.player.position);
+    assert_eq!(a.score, b.score);
+}
+
+#[test]
+fn snapshot_continues_the_game() {
+    let mut sim = seeded(13);
+    run(&mut sim, 200);
+    let path = temp_file("snapshot.json");
+    sim.snapshot().save(&path).unwrap();
+    let snapshot = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut resumed = Simulation::from_snapshot(snapshot);
+    assert_eq!(resumed.ticks(), sim.ticks());
+    run(&mut sim, 200);
+    run(&mut resumed, 200);
+    assert_eq!(resumed.ticks(), sim.ticks());
+    let (a, b) = (resumed.frame(1.).world, sim.frame(1.).world);
+    assert_eq!(a.player.position, b.player.position);
+    assert_eq!(a.score, b.score);
+    assert_eq!(a.bots.len(), b.bots.len());
+    for (a, b) in a.bots.iter().zip(&b.bots) {
+        assert_eq!(a.car.position, b.car.position);
+    }
+}
+
+#[test]
+fn replay_keeps_the_order_of_commands_and_configs() {
+    let mut sim = seeded(17);
+    run(&mut sim, 30);
+    // The jump reads the old timeout, then the new config applies
+    sim.apply(Command::Press(Control::Jump));
+    sim.reconfigure(GameConfig {
+        jump_timeout: 2.,
+        ..sim.config.clone()
+    });
+    run(&mut sim, 30);
+
+    let played = sim.replay().play();
+    assert_eq!(played.ticks(), sim.ticks());
+    assert_eq!(played.config.jump_timeout, 2.);
+    assert_eq!(played.state().jump_timeout, sim.state().jump_timeout);
+}
+
+#[test]
+fn shooting_needs_a_view_down_the_tunel() {
+    let mut sim = seeded(19);
+    let stock = sim.state().bullets;
+    sim.apply(Command::Press(Control::Stare));
+    sim.apply(Command::Press(Control::Shoot));
+    assert_eq!(sim.state().bullets, stock - 1);
+
+    // Leaving the first-person view for the top-down one stops staring
+    sim.apply(Command::Press(Control::NextCamera));
+    assert!(sim.state().rotate_cam);
+    sim.apply(Command::Press(Control::NextCamera));
+    assert!(!sim.state().rotate_cam);
+    sim.apply(Command::Press(Control::Stare));
+    sim.apply(Command::Press(Control::Shoot));
+    assert!(!sim.state().rotate_cam);
+    assert_eq!(sim.state().bullets, stock - 1);
+}
@@ -0,0 +1,39 @@
+use boxcrash::game::{FixedStep, Tunel};
+use boxcrash::GameConfig;
+
+#[test]
+fn time_is_released_in_whole_updates() {
+    // Updates of 0.25 s, exact in binary
+    let mut step = FixedStep::new(4, 5);
+    assert_eq!(step.advance(0.625), 2);
+    assert_eq!(step.alpha(), 0.5);
+    // The rest is kept for the next frame
+    assert_eq!(step.advance(0.125), 1);
+    assert_eq!(step.alpha(), 0.);
+}
+
+#[test]
+fn catch_up_is_capped() {
+    let mut step = FixedStep::new(60, 5);
+    assert_eq!(step.advance(1.), 5);
+    assert!(step.alpha() < 1.);
+    // Time beyond the cap is dropped, not run later
+    assert_eq!(step.advance(0.), 0);
+}
+
+#[test]
+fn tunel_interpolates_across_the_wrap_around() {
+    let config = GameConfig::default();
+    let mut prev = Tunel::new(&config);
+    prev.divider_state = 1.;
+    let period = 2. * prev.divider.y;
+    let mut tunel = prev.clone();
+    // Move 2 units, the dividers wrap to the other end
+    tunel.update(1., 2.);
+    assert!((tunel.divider_state - (period - 1.)).abs() < 1e-9);
+
+    let state = |alpha: f64| tunel.interpolate(&prev, alpha).divider_state;
+    assert!((state(0.25) - 0.5).abs() < 1e-9);
+    assert!((state(0.75) - (period - 0.5)).abs() < 1e-9);
+    assert!((state(1.) - tunel.divider_state).abs() < 1e-9);
+}